@@ -1,5 +1,7 @@
 use std::fmt;
 use clap::{Parser, ValueEnum};
+use crate::hygiene::{Hygiene, Policy};
+use crate::limits::Limits;
 
 #[derive(ValueEnum, Clone, Debug)]
 pub enum SchemaType {
@@ -28,4 +30,65 @@ pub struct Cli {
     #[arg(default_value_t = SchemaType::Tsv)]
     /// Schema type. schematch support tsv and json, If not provided tsv will be used.
     pub schema_type: SchemaType,
+
+    #[clap(long)]
+    /// Reject properties which are not declared, in every object of a json schema.
+    pub no_extra_properties: bool,
+
+    #[clap(long, value_enum, default_value_t = Policy::Tolerate)]
+    /// What to do with CRLF line endings.
+    pub crlf: Policy,
+
+    #[clap(long, value_enum, default_value_t = Policy::Tolerate)]
+    /// What to do with a UTF-8 byte order mark at the start of the input.
+    pub bom: Policy,
+
+    #[clap(long, value_enum, default_value_t = Policy::Tolerate)]
+    /// What to do with spaces and tabs at the end of lines.
+    pub trailing_whitespace: Policy,
+
+    #[clap(long, value_enum, default_value_t = Policy::Tolerate)]
+    /// What to do with input which does not end with a newline.
+    pub final_newline: Policy,
+
+    #[clap(long, default_value_t = 1 << 20)]
+    /// Maximum bytes of a line.
+    pub max_line_length: usize,
+
+    #[clap(long, default_value_t = 10000)]
+    /// Maximum fields of a tsv line.
+    pub max_fields: usize,
+
+    #[clap(long, default_value_t = 64 << 20)]
+    /// Maximum bytes of a json document.
+    pub max_document_size: usize,
+
+    #[clap(long, default_value_t = 128)]
+    /// Maximum nesting of arrays and objects in a json document.
+    pub max_depth: usize,
+
+    #[clap(long, default_value_t = 1000)]
+    /// Maximum errors to report. Errors beyond it are only counted.
+    pub max_errors: usize,
+}
+
+impl Cli {
+    pub fn hygiene(&self) -> Hygiene {
+        Hygiene {
+            crlf: self.crlf,
+            bom: self.bom,
+            trailing_whitespace: self.trailing_whitespace,
+            final_newline: self.final_newline,
+        }
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            max_line_length: self.max_line_length,
+            max_fields: self.max_fields,
+            max_document_size: self.max_document_size,
+            max_depth: self.max_depth,
+            max_errors: self.max_errors,
+        }
+    }
 }
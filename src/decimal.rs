@@ -0,0 +1,207 @@
+use std::cmp::Ordering;
+
+/// A decimal number as written, compared digit by digit without going
+/// through `f64`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Decimal {
+    negative: bool,
+    integer: String,
+    fraction: String,
+}
+
+impl Decimal {
+    /// Parse `[+-]digits[.digits]`. Exponents are not accepted.
+    pub fn parse(text: &str) -> Option<Decimal> {
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (integer, fraction) = match unsigned.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (unsigned, ""),
+        };
+
+        let digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+        if integer.is_empty() || !digits(integer) || !digits(fraction) || unsigned.ends_with('.') {
+            return None;
+        }
+
+        Some(Decimal {
+            negative,
+            integer: integer.to_string(),
+            fraction: fraction.to_string(),
+        })
+    }
+
+    /// The exact sum of two decimals.
+    pub fn add(&self, other: &Decimal) -> Decimal {
+        let width = self.fraction.len().max(other.fraction.len());
+        let digits = |decimal: &Decimal| format!("{}{:0<width$}", decimal.integer, decimal.fraction);
+        let (lhs, rhs) = (digits(self), digits(other));
+
+        let (negative, magnitude) = if self.negative == other.negative {
+            (self.negative, add_digits(&lhs, &rhs))
+        } else if self.cmp_magnitude(other) == Ordering::Less {
+            (other.negative, sub_digits(&rhs, &lhs))
+        } else {
+            (self.negative, sub_digits(&lhs, &rhs))
+        };
+
+        let magnitude = format!("{:0>1$}", magnitude, width + 1);
+        let (integer, fraction) = magnitude.split_at(magnitude.len() - width);
+        let integer = match integer.trim_start_matches('0') {
+            "" => "0",
+            integer => integer,
+        };
+        let sum = Decimal {
+            negative,
+            integer: integer.to_string(),
+            fraction: fraction.to_string(),
+        };
+        Decimal { negative: negative && !sum.is_zero(), ..sum }
+    }
+
+    /// Number of integer digits, not counting leading zeros.
+    fn integer_digits(&self) -> usize {
+        self.integer.trim_start_matches('0').len()
+    }
+
+    fn is_zero(&self) -> bool {
+        self.integer.chars().chain(self.fraction.chars()).all(|c| c == '0')
+    }
+
+    fn cmp_magnitude(&self, other: &Decimal) -> Ordering {
+        let (lhs, rhs) = (self.integer.trim_start_matches('0'), other.integer.trim_start_matches('0'));
+        lhs.len()
+            .cmp(&rhs.len())
+            .then_with(|| lhs.cmp(rhs))
+            .then_with(|| {
+                let width = self.fraction.len().max(other.fraction.len());
+                format!("{:0<width$}", self.fraction).cmp(&format!("{:0<width$}", other.fraction))
+            })
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Decimal) -> Ordering {
+        let sign = |d: &Decimal| if d.is_zero() { 0 } else if d.negative { -1 } else { 1 };
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if sign(self) < 0 => other.cmp_magnitude(self),
+            Ordering::Equal => self.cmp_magnitude(other),
+            ordering => ordering,
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Decimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let sign = if self.negative { "-" } else { "" };
+        match self.fraction.as_str() {
+            "" => write!(f, "{}{}", sign, self.integer),
+            fraction => write!(f, "{}{}.{}", sign, self.integer, fraction),
+        }
+    }
+}
+
+/// Add two unsigned numbers written in digits.
+fn add_digits(lhs: &str, rhs: &str) -> String {
+    let (mut lhs, mut rhs) = (lhs.bytes().rev(), rhs.bytes().rev());
+    let mut digits = vec![];
+    let mut carry = 0;
+    loop {
+        let (a, b) = (lhs.next(), rhs.next());
+        if a.is_none() && b.is_none() && carry == 0 {
+            break;
+        }
+        let sum = a.map_or(0, |a| a - b'0') + b.map_or(0, |b| b - b'0') + carry;
+        digits.push(b'0' + sum % 10);
+        carry = sum / 10;
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+/// Subtract unsigned numbers written in digits, where `lhs` is not smaller.
+fn sub_digits(lhs: &str, rhs: &str) -> String {
+    let (lhs, mut rhs) = (lhs.bytes().rev(), rhs.bytes().rev());
+    let mut digits = vec![];
+    let mut borrow = 0;
+    for a in lhs {
+        let b = rhs.next().map_or(0, |b| b - b'0') + borrow;
+        let a = a - b'0' + 10;
+        digits.push(b'0' + (a - b) % 10);
+        borrow = if a - b < 10 { 1 } else { 0 };
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+/// `decimal(precision, scale)`: at most `precision` digits, of which at most
+/// `scale` are after the decimal point, optionally bounded by `min` and `max`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecimalType {
+    pub precision: usize,
+    pub scale: usize,
+    pub min: Option<Decimal>,
+    pub max: Option<Decimal>,
+}
+
+impl DecimalType {
+    /// Build the type from arguments such as `["10", "2", "min=0"]`.
+    pub fn from_args(args: &[&str]) -> Option<DecimalType> {
+        let (precision, scale, bounds) = match args {
+            [precision, scale, bounds @ ..] => (precision, scale, bounds),
+            _ => return None,
+        };
+        let mut type_ = DecimalType {
+            precision: precision.parse().ok()?,
+            scale: scale.parse().ok()?,
+            min: None,
+            max: None,
+        };
+        if type_.precision == 0 || type_.scale > type_.precision {
+            return None;
+        }
+
+        for bound in bounds {
+            match bound.split_once('=').map(|(k, v)| (k.trim(), Decimal::parse(v.trim()))) {
+                Some(("min", Some(min))) => type_.min = Some(min),
+                Some(("max", Some(max))) => type_.max = Some(max),
+                _ => return None,
+            }
+        }
+
+        match (&type_.min, &type_.max) {
+            (Some(min), Some(max)) if min > max => None,
+            _ => Some(type_),
+        }
+    }
+
+    pub fn accepts(&self, text: &str) -> bool {
+        let Some(decimal) = Decimal::parse(text) else {
+            return false;
+        };
+
+        decimal.integer_digits() <= self.precision - self.scale
+            && decimal.fraction.len() <= self.scale
+            && self.min.as_ref().is_none_or(|min| &decimal >= min)
+            && self.max.as_ref().is_none_or(|max| &decimal <= max)
+    }
+}
+
+impl std::fmt::Display for DecimalType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "decimal({}, {}", self.precision, self.scale)?;
+        if let Some(min) = &self.min {
+            write!(f, ", min={}", min)?;
+        }
+        if let Some(max) = &self.max {
+            write!(f, ", max={}", max)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -0,0 +1,278 @@
+use std::io::BufRead;
+use clap::ValueEnum;
+use thiserror::Error;
+
+/// What to do with an input which has a hygiene issue. Whatever the policy,
+/// the input is printed as it is.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Policy {
+    Reject,
+    Warn,
+    Tolerate,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Issue {
+    Crlf,
+    Bom,
+    TrailingWhitespace,
+    MissingFinalNewline,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Issue::Crlf => write!(f, "CRLF line ending"),
+            Issue::Bom => write!(f, "UTF-8 byte order mark"),
+            Issue::TrailingWhitespace => write!(f, "Trailing whitespace"),
+            Issue::MissingFinalNewline => write!(f, "Missing final newline"),
+        }
+    }
+}
+
+/// The policy for each issue, set from the command line.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Hygiene {
+    pub crlf: Policy,
+    pub bom: Policy,
+    pub trailing_whitespace: Policy,
+    pub final_newline: Policy,
+}
+
+/// A line of input. `text` has the byte order mark and line ending removed,
+/// and `raw` is the line as it was read. A line longer than the limit is not
+/// kept: only its `length` is known.
+#[derive(Debug, PartialEq)]
+pub struct Line {
+    pub number: usize,
+    pub text: String,
+    pub raw: String,
+    pub length: usize,
+    pub too_long: bool,
+    start: usize,
+}
+
+impl Line {
+    /// The raw line with its text replaced, keeping the byte order mark and
+    /// line ending.
+    pub fn replace_text(&self, text: &str) -> String {
+        let end = self.start + self.text.len();
+        format!("{}{}{}", &self.raw[..self.start], text, &self.raw[end..])
+    }
+}
+
+/// Reads lines, keeping their line endings, and records the issues found.
+pub struct Lines {
+    reader: Box<dyn BufRead>,
+    max_line_length: usize,
+    number: usize,
+    issues: Vec<HygieneError>,
+    /// The number of a line which is not UTF-8, where reading stopped.
+    pub invalid_utf8: Option<usize>,
+}
+
+impl Lines {
+    pub fn new(reader: Box<dyn BufRead>, max_line_length: usize) -> Lines {
+        Lines {
+            reader,
+            max_line_length,
+            number: 0,
+            issues: vec![],
+            invalid_utf8: None,
+        }
+    }
+
+    /// Record an issue at the current line. Only the first line numbers are
+    /// kept, and the rest are counted.
+    fn found(&mut self, issue: Issue) {
+        let index = match self.issues.iter().position(|error| error.issue == issue) {
+            Some(index) => index,
+            None => {
+                self.issues.push(HygieneError { issue, lines: vec![], count: 0 });
+                self.issues.len() - 1
+            }
+        };
+        let error = &mut self.issues[index];
+        if error.lines.len() < SHOWN {
+            error.lines.push(self.number);
+        }
+        error.count += 1;
+    }
+
+    /// Read up to and including the next newline, keeping at most the limit
+    /// and the line ending. Returns the bytes kept, the full length, whether
+    /// the line ends with a newline and whether a CR comes before it, which
+    /// is known even when the bytes are cut.
+    fn read_line(&mut self) -> (Vec<u8>, usize, bool, bool) {
+        let capacity = self.max_line_length.saturating_add(2);
+        let mut bytes = vec![];
+        let mut length = 0;
+        let mut newline = false;
+        let mut last = None;
+        let mut crlf = false;
+        while !newline {
+            let buffer = self.reader.fill_buf().unwrap();
+            if buffer.is_empty() {
+                break;
+            }
+            let chunk;
+            (chunk, newline) = match buffer.iter().position(|&b| b == b'\n') {
+                Some(i) => (&buffer[..=i], true),
+                None => (buffer, false),
+            };
+            if newline {
+                crlf = chunk.len().checked_sub(2).map_or(last, |i| Some(chunk[i])) == Some(b'\r');
+            }
+            last = chunk.last().copied();
+            let kept = chunk.len().min(capacity.saturating_sub(bytes.len()));
+            bytes.extend_from_slice(&chunk[..kept]);
+            length += chunk.len();
+
+            let consumed = chunk.len();
+            self.reader.consume(consumed);
+        }
+        (bytes, length, newline, crlf)
+    }
+}
+
+impl Iterator for Lines {
+    type Item = Line;
+
+    fn next(&mut self) -> Option<Line> {
+        let (bytes, length, newline, crlf) = self.read_line();
+        if length == 0 {
+            return None;
+        }
+        self.number += 1;
+
+        let ending = match crlf {
+            true => 2,
+            false => newline as usize,
+        };
+        if length - ending > self.max_line_length {
+            if crlf {
+                self.found(Issue::Crlf);
+            }
+            if !newline {
+                self.found(Issue::MissingFinalNewline);
+            }
+            return Some(Line {
+                number: self.number,
+                text: String::new(),
+                raw: String::new(),
+                length: length - ending,
+                too_long: true,
+                start: 0,
+            });
+        }
+        let Ok(raw) = String::from_utf8(bytes) else {
+            self.invalid_utf8 = Some(self.number);
+            return None;
+        };
+
+        let mut text = raw.as_str();
+        let mut start = 0;
+        if self.number == 1 {
+            if let Some(rest) = text.strip_prefix('\u{FEFF}') {
+                self.found(Issue::Bom);
+                start = raw.len() - rest.len();
+                text = rest;
+            }
+        }
+        match text.strip_suffix('\n') {
+            Some(rest) => text = rest,
+            None => self.found(Issue::MissingFinalNewline),
+        }
+        if let Some(rest) = text.strip_suffix('\r') {
+            self.found(Issue::Crlf);
+            text = rest;
+        }
+        if text.ends_with([' ', '\t']) {
+            self.found(Issue::TrailingWhitespace);
+        }
+
+        let text = text.to_string();
+        Some(Line {
+            number: self.number,
+            length: text.len(),
+            too_long: false,
+            text,
+            raw,
+            start,
+        })
+    }
+}
+
+impl Hygiene {
+    fn policy(&self, issue: Issue) -> Policy {
+        match issue {
+            Issue::Crlf => self.crlf,
+            Issue::Bom => self.bom,
+            Issue::TrailingWhitespace => self.trailing_whitespace,
+            Issue::MissingFinalNewline => self.final_newline,
+        }
+    }
+
+    /// Print a warning for each issue to warn about, and fail with the issues
+    /// to reject.
+    pub fn check(&self, lines: &Lines) -> Result<(), HygieneErrors> {
+        let mut errors = vec![];
+
+        for issue in [Issue::Bom, Issue::Crlf, Issue::TrailingWhitespace, Issue::MissingFinalNewline] {
+            let Some(error) = lines.issues.iter().find(|error| error.issue == issue) else {
+                continue;
+            };
+
+            let error = error.clone();
+            match self.policy(issue) {
+                Policy::Reject => errors.push(error),
+                Policy::Warn => eprintln!("Warning: {}", error),
+                Policy::Tolerate => {}
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(HygieneErrors(errors))
+        }
+    }
+}
+
+/// How many line numbers are shown for an issue.
+const SHOWN: usize = 5;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub struct HygieneError {
+    pub issue: Issue,
+    /// The first lines with the issue, up to `SHOWN`.
+    pub lines: Vec<usize>,
+    pub count: usize,
+}
+
+impl std::fmt::Display for HygieneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let shown = self.lines
+            .iter()
+            .map(|number| number.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        match self.count {
+            1 => write!(f, "{} at line {}", self.issue, shown),
+            n if n <= SHOWN => write!(f, "{} at lines {}", self.issue, shown),
+            n => write!(f, "{} at lines {} and {} more", self.issue, shown, n - SHOWN),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub struct HygieneErrors(pub Vec<HygieneError>);
+
+impl std::fmt::Display for HygieneErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for error in &self.0 {
+            writeln!(f, "Error: {}", error)?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
 mod data;
+mod number;
+mod items;
+mod string;
 mod lexer;
 mod parser;
 mod schema;
@@ -1,3 +1,8 @@
+use crate::decimal::DecimalType;
+use super::items::Items;
+use super::number::NumberType;
+use super::string::StringType;
+
 #[derive(Debug, PartialEq)]
 pub struct Location {
     pub start: usize,
@@ -14,13 +19,27 @@ impl std::fmt::Display for Location {
 pub enum TokenKind {
     Identifier(String),
     Colon,
+    Question,
     Comma,
+    Semicolon,
     LeftBrace,
     RightBrace,
+    LeftBraceBar,
+    RightBraceBar,
     LessThan,
     GreaterThan,
+    LeftBracket,
+    RightBracket,
+    Ellipsis,
+    LeftParen,
+    RightParen,
+    Equal,
     VerticalBar,
-    EOF,
+    Ampersand,
+    Number(String),
+    String(String),
+    Regex(String),
+    Eof,
 }
 
 #[derive(Debug, PartialEq)]
@@ -33,22 +52,95 @@ pub struct Token {
 #[derive(Debug, PartialEq)]
 pub enum Type {
     Null,
-    String,
-    Number,
+    String(StringType),
+    Number(NumberType),
+    Integer(NumberType),
+    Float(NumberType),
+    Decimal(DecimalType),
     Boolean,
+    /// A single value, such as `"admin"`, `1` or `true`.
+    Literal(serde_json::Value),
     Object(Box<Object>),
     Array(Box<Array>),
+    Tuple(Box<Tuple>),
+    Record(Box<Record>),
+    /// A type defined with `type <Name> = <type>;`, looked up by name when
+    /// validating so that it may refer to itself.
+    Reference(String),
+    /// Types which a value must all match, written as `A & B`. The
+    /// properties of its objects are merged.
+    Intersection(Vec<Type>),
 }
 
-impl ToString for Type {
-    fn to_string(&self) -> String {
+/// The types defined before the schema, by name.
+pub type Definitions = std::collections::BTreeMap<String, Vec<Type>>;
+
+impl Type {
+    /// Make every object in the type closed, for `--no-extra-properties`.
+    pub fn close(&mut self) {
+        match self {
+            Type::Object(object) => object.close(),
+            Type::Array(array) => array.close(),
+            Type::Tuple(tuple) => tuple.close(),
+            Type::Record(record) => record.close(),
+            Type::Intersection(types) => types.iter_mut().for_each(Type::close),
+            _ => {}
+        }
+    }
+
+    /// Call `f` with the type and every type nested in it.
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Type)) {
+        f(self);
+        match self {
+            Type::Object(object) => object.properties.iter().flat_map(|property| &property.types).for_each(|type_| type_.walk(f)),
+            Type::Array(array) => array.types.iter().for_each(|type_| type_.walk(f)),
+            Type::Tuple(tuple) => tuple.elements
+                .iter()
+                .flat_map(|element| &element.types)
+                .chain(tuple.rest.iter().flatten())
+                .for_each(|type_| type_.walk(f)),
+            Type::Record(record) => record.types.iter().for_each(|type_| type_.walk(f)),
+            Type::Intersection(types) => types.iter().for_each(|type_| type_.walk(f)),
+            _ => {}
+        }
+    }
+
+    /// The types this type intersects, looking through defined types and
+    /// nested intersections, as the two objects of `E` in `E & {id: string}`
+    /// with `type E = {x: number} & {y: number}`.
+    pub fn intersected<'a>(&'a self, definitions: &'a Definitions) -> Vec<&'a Type> {
         match self {
-            Type::Null => "null".to_string(),
-            Type::String => "string".to_string(),
-            Type::Number => "number".to_string(),
-            Type::Boolean => "boolean".to_string(),
-            Type::Object(_) => "object".to_string(),
-            Type::Array(_) => "array".to_string(),
+            Type::Reference(name) => match definitions.get(name).map(Vec::as_slice) {
+                Some([type_]) => type_.intersected(definitions),
+                _ => vec![self],
+            },
+            Type::Intersection(types) => types.iter().flat_map(|type_| type_.intersected(definitions)).collect(),
+            _ => vec![self],
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Type::Null => write!(f, "null"),
+            Type::String(string) => write!(f, "{}", string),
+            Type::Number(number) => number.write(f, "number"),
+            Type::Integer(number) => number.write(f, "integer"),
+            Type::Float(number) => number.write(f, "float"),
+            Type::Decimal(decimal) => write!(f, "{}", decimal),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Literal(value) => write!(f, "{}", value),
+            Type::Object(_) => write!(f, "object"),
+            Type::Array(_) => write!(f, "array"),
+            Type::Tuple(tuple) => write!(f, "{}", tuple),
+            Type::Record(_) => write!(f, "object"),
+            Type::Reference(name) => write!(f, "{}", name),
+            Type::Intersection(types) => write!(f, "{}", types
+                .iter()
+                .map(|type_| type_.to_string())
+                .collect::<Vec<String>>()
+                .join(" & ")),
         }
     }
 }
@@ -57,36 +149,179 @@ impl ToString for Type {
 pub struct Property {
     pub name: String,
     pub types: Vec<Type>,
+    /// Declared as `name?: type`, so the key may be missing. A key which is
+    /// present is still checked against the types, even when it is `null`.
+    pub optional: bool,
 }
 
 impl Property {
-    pub fn new(name: String, types: Vec<Type>) -> Property {
+    pub fn new(name: String, types: Vec<Type>, optional: bool) -> Property {
         Property {
             name,
             types,
+            optional,
         }
     }
+
+    /// Whether the other property is declared alike, with its types in any
+    /// order.
+    pub fn same_as(&self, other: &Property) -> bool {
+        self.optional == other.optional
+            && self.types.iter().all(|type_| other.types.contains(type_))
+            && other.types.iter().all(|type_| self.types.contains(type_))
+    }
 }
 
+/// An object type. A closed object, written as `{| ... |}`, rejects keys
+/// which are not declared.
 #[derive(Debug, PartialEq)]
 pub struct Object {
     pub properties: Vec<Property>,
+    pub closed: bool,
 }
 
 impl Object {
-    pub fn new() -> Object {
-        Object { properties: vec![] }
+    pub fn new(closed: bool) -> Object {
+        Object { properties: vec![], closed }
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.properties
+            .iter_mut()
+            .flat_map(|property| property.types.iter_mut())
+            .for_each(Type::close);
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub struct Array {
     pub types: Vec<Type>,
+    pub items: Items,
 }
 
 impl Array {
-    pub fn new(types: Vec<Type>) -> Array {
-        Array { types }
+    pub fn new(types: Vec<Type>, items: Items) -> Array {
+        Array { types, items }
+    }
+
+    pub fn close(&mut self) {
+        self.types.iter_mut().for_each(Type::close);
+    }
+}
+
+/// A tuple element, written as `type` or, if it may be missing, `type?`.
+#[derive(Debug, PartialEq)]
+pub struct Element {
+    pub types: Vec<Type>,
+    pub optional: bool,
+}
+
+/// An array with a type for each position, such as `[number, number]`. The
+/// optional elements come after the required ones, and `...T[]` gives the
+/// type of any elements after them.
+#[derive(Debug, PartialEq)]
+pub struct Tuple {
+    pub elements: Vec<Element>,
+    pub rest: Option<Vec<Type>>,
+}
+
+impl Tuple {
+    pub fn new(elements: Vec<Element>, rest: Option<Vec<Type>>) -> Tuple {
+        Tuple { elements, rest }
+    }
+
+    pub fn close(&mut self) {
+        self.elements
+            .iter_mut()
+            .flat_map(|element| element.types.iter_mut())
+            .chain(self.rest.iter_mut().flatten())
+            .for_each(Type::close);
+    }
+
+    /// The number of elements accepted, as `2`, `1 to 2` or `at least 1`.
+    pub fn arity(&self) -> String {
+        let required = self.elements.iter().filter(|element| !element.optional).count();
+        match (self.rest.is_some(), self.elements.len()) {
+            (true, _) => format!("at least {}", required),
+            (false, all) if all == required => required.to_string(),
+            (false, all) => format!("{} to {}", required, all),
+        }
+    }
+}
+
+impl std::fmt::Display for Tuple {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let types = |types: &Vec<Type>| types.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(" | ");
+        let mut elements = self.elements
+            .iter()
+            .map(|element| format!("{}{}", types(&element.types), if element.optional { "?" } else { "" }))
+            .collect::<Vec<String>>();
+        if let Some(rest) = &self.rest {
+            elements.push(format!("...{}[]", types(rest)));
+        }
+        write!(f, "[{}]", elements.join(", "))
+    }
+}
+
+/// The keys a record accepts: those of a string type, those matching a
+/// regex, or those among a union of string literals.
+#[derive(Debug)]
+pub enum Key {
+    String(StringType),
+    Pattern(regex::Regex),
+    Literals(Vec<String>),
+}
+
+impl Key {
+    pub fn accepts(&self, key: &str) -> bool {
+        match self {
+            Key::String(string) => string.accepts(key),
+            Key::Pattern(pattern) => pattern.is_match(key),
+            Key::Literals(literals) => literals.iter().any(|literal| literal == key),
+        }
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Key) -> bool {
+        match (self, other) {
+            (Key::String(a), Key::String(b)) => a == b,
+            (Key::Pattern(a), Key::Pattern(b)) => a.as_str() == b.as_str(),
+            (Key::Literals(a), Key::Literals(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Key::String(string) => write!(f, "{}", string),
+            Key::Pattern(pattern) => write!(f, "/{}/", pattern.as_str()),
+            Key::Literals(literals) => write!(f, "{}", literals
+                .iter()
+                .map(|literal| serde_json::Value::String(literal.clone()).to_string())
+                .collect::<Vec<String>>()
+                .join(" or ")),
+        }
+    }
+}
+
+/// An object with keys not known in advance, written as `Record<K, V>`.
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    pub key: Key,
+    pub types: Vec<Type>,
+}
+
+impl Record {
+    pub fn new(key: Key, types: Vec<Type>) -> Record {
+        Record { key, types }
+    }
+
+    pub fn close(&mut self) {
+        self.types.iter_mut().for_each(Type::close);
     }
 }
 
@@ -96,3 +331,23 @@ pub enum Value {
     Array(Array),
     Types(Vec<Type>),
 }
+
+impl Value {
+    /// Call `f` with every type in the schema.
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a Type)) {
+        let types = match self {
+            Value::Object(object) => object.properties.iter().flat_map(|property| &property.types).collect::<Vec<&Type>>(),
+            Value::Array(array) => array.types.iter().collect(),
+            Value::Types(types) => types.iter().collect(),
+        };
+        types.into_iter().for_each(|type_| type_.walk(f));
+    }
+
+    pub fn close(&mut self) {
+        match self {
+            Value::Object(object) => object.close(),
+            Value::Array(array) => array.close(),
+            Value::Types(types) => types.iter_mut().for_each(Type::close),
+        }
+    }
+}
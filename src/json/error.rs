@@ -25,13 +25,62 @@ pub enum SchemaError {
     #[error(r#"
         Error: Invalid Type {type_} found at {location}
 
-        Available types: integer, float, string, boolean, null
+        Available types: null, string, number, integer, float, decimal(<precision>, <scale>), boolean
+        Number constraints: min=<n>, max=<n>, multipleOf=<n>, and i64 or u64 for integer
+        Literals: "<string>", <number>, true, false
+        String refinements: min=<n>, max=<n>, pattern=/<regex>/, format=date-time|email|uuid|uri|ipv4|ipv6
+        Array constraints: min=<n>, max=<n>, nonempty, unique, unique=<key path>
     "#)]
     InvalidType {
         type_: String,
         location: Location,
     },
 
+    #[error(r#"
+        Error: Invalid Record key {key} found at {location}
+
+        Available keys: string, /<regex>/, or a union of string literals such as "a" | "b"
+    "#)]
+    InvalidRecordKey {
+        key: String,
+        location: Location,
+    },
+
+    #[error("Error: Invalid tuple found at {location}, {reason}")]
+    InvalidTuple {
+        reason: String,
+        location: Location,
+    },
+
+    #[error(r#"
+        Error: Undefined type {name} found at {location}
+
+        Available types: null, string, number, integer, float, decimal(<precision>, <scale>), boolean
+        Or a type defined before the schema with: type {name} = <type>;
+    "#)]
+    UndefinedType {
+        name: String,
+        location: Location,
+    },
+
+    #[error("Error: Type {name} is defined twice, found at {location}")]
+    DuplicateType {
+        name: String,
+        location: Location,
+    },
+
+    #[error("Error: Type {name} refers to itself outside any object or array")]
+    UnguardedRecursion {
+        name: String,
+    },
+
+    #[error("Error: Property {name} is declared differently in an intersection, as {name}{left} and {name}{right}")]
+    ConflictingProperty {
+        name: String,
+        left: String,
+        right: String,
+    },
+
     #[error("Error: Unterminated string found at {location}")]
     UnterminatedString {
         location: Location,
@@ -50,7 +99,7 @@ impl From<SchemaError> for SchemaErrors {
 impl std::fmt::Display for SchemaErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         for error in &self.0 {
-            write!(f, "{}\n", error)?;
+            writeln!(f, "{}", error)?;
         }
         Ok(())
     }
@@ -66,15 +115,72 @@ pub enum ValidationError {
         name: String,
     },
 
+    #[error("Error: Unexpected property: {path}")]
+    UnexpectedProperty {
+        path: String,
+    },
+
     #[error(r#"
-        Error: Data type mismatch
+        Error: Data type mismatch at {path}
 
         Expected type is {types}, But Found: {value}
     "#)]
     DataTypeMismatch {
+        path: String,
         types: String,
         value: String
     },
+
+    #[error("Error: Unexpected key at {path}, Expected key is {expected}")]
+    UnexpectedKey {
+        path: String,
+        expected: String,
+    },
+
+    #[error("Error: Array length mismatch at {path}, Expected {expected} items, But Found: {found}")]
+    ArrayLengthMismatch {
+        path: String,
+        expected: String,
+        found: usize,
+    },
+
+    #[error("Error: Duplicate items at {path}, Items {indices} have the same {key}: {value}")]
+    DuplicateItems {
+        path: String,
+        indices: String,
+        key: String,
+        value: String,
+    },
+
+    #[error("Error: Unknown discriminant value at {path}, Expected {expected}, But Found: {value}")]
+    UnknownDiscriminant {
+        path: String,
+        expected: String,
+        value: String,
+    },
+
+    #[error("Error: Tuple length mismatch at {path}, Expected {expected} elements, But Found: {found}")]
+    TupleLengthMismatch {
+        path: String,
+        expected: String,
+        found: usize,
+    },
+
+    #[error("Error: Document too large, Expected at most {limit} bytes")]
+    DocumentTooLarge {
+        limit: usize,
+    },
+
+    #[error("Error: Document too deep, Expected at most {limit} levels of nesting")]
+    TooDeep {
+        limit: usize,
+    },
+
+    #[error("Error: {count} more errors, beyond the limit of {limit} errors")]
+    ErrorsOmitted {
+        count: usize,
+        limit: usize,
+    },
 }
 
 impl From<SerdeError> for ValidationError {
@@ -84,32 +190,51 @@ impl From<SerdeError> for ValidationError {
 }
 
 #[derive(Error, Debug, PartialEq)]
-pub struct ValidationErrors(pub Vec<ValidationError>);
+pub struct ValidationErrors {
+    pub errors: Vec<ValidationError>,
+    /// Errors beyond the limit, which are counted but not kept.
+    pub omitted: usize,
+    pub limit: usize,
+}
 
 impl ValidationErrors {
-    pub fn new() -> ValidationErrors {
-        ValidationErrors(vec![])
+    pub fn new(limit: usize) -> ValidationErrors {
+        ValidationErrors { errors: vec![], omitted: 0, limit }
+    }
+
+    pub fn push(self: &mut ValidationErrors, error: ValidationError) {
+        if self.errors.len() < self.limit {
+            self.errors.push(error);
+        } else {
+            self.omitted += 1;
+        }
     }
 
     pub fn extend(self: &mut ValidationErrors, other: ValidationErrors) {
-        self.0.extend(other.0);
+        for error in other.errors {
+            self.push(error);
+        }
+        self.omitted += other.omitted;
     }
 
     pub fn is_empty(self: &ValidationErrors) -> bool {
-        self.0.is_empty()
+        self.errors.is_empty() && self.omitted == 0
     }
 }
 
 impl From<ValidationError> for ValidationErrors {
     fn from(error: ValidationError) -> ValidationErrors {
-        ValidationErrors(vec![error])
+        ValidationErrors { errors: vec![error], omitted: 0, limit: usize::MAX }
     }
 }
 
 impl std::fmt::Display for ValidationErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        for error in &self.0 {
-            write!(f, "{}\n", error)?;
+        for error in &self.errors {
+            writeln!(f, "{}", error)?;
+        }
+        if self.omitted > 0 {
+            writeln!(f, "{}", ValidationError::ErrorsOmitted { count: self.omitted, limit: self.limit })?;
         }
         Ok(())
     }
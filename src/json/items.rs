@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use serde_json::Value;
+use super::number;
+
+/// What makes two items of a unique array the same: the whole value, or the
+/// value at a key path such as `id` or `owner.id`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Unique {
+    Value,
+    Key(Vec<String>),
+}
+
+/// Constraints of `Array<T>`, written as arguments such as
+/// `Array<string>(min=1, max=10, unique)`. `nonempty` is short for `min=1`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Items {
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    pub unique: Option<Unique>,
+}
+
+impl Items {
+    pub fn from_args(args: &[&str]) -> Option<Items> {
+        let mut items = Items::default();
+        for arg in args {
+            match arg.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("min", min)) => items.min = Some(min.parse().ok()?),
+                Some(("max", max)) => items.max = Some(max.parse().ok()?),
+                Some(("unique", path)) if !path.is_empty() => {
+                    items.unique = Some(Unique::Key(path.split('.').map(|key| key.to_string()).collect()));
+                }
+                Some(_) => return None,
+                None => match *arg {
+                    "nonempty" => items.min = Some(1),
+                    "unique" => items.unique = Some(Unique::Value),
+                    _ => return None,
+                },
+            }
+        }
+        match (items.min, items.max) {
+            (Some(min), Some(max)) if min > max => None,
+            _ => Some(items),
+        }
+    }
+
+    /// The number of items accepted, as `at least 1`, `at most 10` or
+    /// `1 to 10`, or `None` if any number is.
+    pub fn length(&self) -> Option<String> {
+        match (self.min, self.max) {
+            (None, None) => None,
+            (Some(min), None) => Some(format!("at least {}", min)),
+            (None, Some(max)) => Some(format!("at most {}", max)),
+            (Some(min), Some(max)) => Some(format!("{} to {}", min, max)),
+        }
+    }
+
+    pub fn accepts_length(&self, length: usize) -> bool {
+        self.min.is_none_or(|min| length >= min) && self.max.is_none_or(|max| length <= max)
+    }
+
+    /// The indices of items which are the same, with the value they share,
+    /// in the order the values first appear. Items without the key are not
+    /// compared, and numbers are the same when they are equal, as `1` and
+    /// `1.0`.
+    pub fn duplicates(&self, array: &[Value]) -> Vec<(Vec<usize>, String)> {
+        let Some(unique) = &self.unique else {
+            return vec![];
+        };
+
+        let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut order = vec![];
+        for (i, item) in array.iter().enumerate() {
+            let value = match unique {
+                Unique::Value => Some(item),
+                Unique::Key(path) => path.iter().try_fold(item, |value, key| value.get(key)),
+            };
+            let Some(value) = value else { continue };
+            let key = normalize(value).to_string();
+            groups
+                .entry(key.clone())
+                .or_insert_with(|| {
+                    order.push((key, value.to_string()));
+                    vec![]
+                })
+                .push(i);
+        }
+
+        order
+            .into_iter()
+            .filter_map(|(key, value)| Some((groups.remove(&key)?, value)))
+            .filter(|(indices, _)| indices.len() > 1)
+            .collect()
+    }
+}
+
+impl std::fmt::Display for Unique {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Unique::Value => write!(f, "value"),
+            Unique::Key(path) => write!(f, "{}", path.join(".")),
+        }
+    }
+}
+
+/// The value with its numbers written the same way whenever they are equal.
+fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Number(number) => Value::Number(number::canonical(number)),
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        Value::Object(object) => Value::Object(object.iter().map(|(key, value)| (key.clone(), normalize(value))).collect()),
+        value => value.clone(),
+    }
+}
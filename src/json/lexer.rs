@@ -4,6 +4,9 @@ use super::error::{SchemaErrors, SchemaError};
 pub struct Lexer<'a> {
     source: &'a str,
     chars: std::str::Chars<'a>,
+    /// Whether the last token was `=`, so that a value such as `date-time`
+    /// may follow.
+    after_equal: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -11,6 +14,7 @@ impl<'a> Lexer<'a> {
         Lexer {
             source,
             chars: source.chars(),
+            after_equal: false,
         }
     }
 
@@ -18,7 +22,7 @@ impl<'a> Lexer<'a> {
         let mut head = self.read_next_token()?;
         let mut current = &mut head;
 
-        while current.kind != TokenKind::EOF {
+        while current.kind != TokenKind::Eof {
             let token = self.read_next_token()?;
             current.next = Some(Box::new(token));
             current = current.next.as_mut().unwrap();
@@ -35,12 +39,32 @@ impl<'a> Lexer<'a> {
         let start = self.offset();
 
         let kind = match self.chars.next() {
+            Some('{') if self.chars.clone().next() == Some('|') => {
+                self.chars.next();
+                TokenKind::LeftBraceBar
+            }
             Some('{') => TokenKind::LeftBrace,
             Some('}') => TokenKind::RightBrace,
             Some(':') => TokenKind::Colon,
+            Some('?') => TokenKind::Question,
             Some(',') => TokenKind::Comma,
+            Some('&') => TokenKind::Ampersand,
+            Some(';') => TokenKind::Semicolon,
             Some('<') => TokenKind::LessThan,
             Some('>') => TokenKind::GreaterThan,
+            Some('[') => TokenKind::LeftBracket,
+            Some(']') => TokenKind::RightBracket,
+            Some('.') if self.chars.as_str().starts_with("..") => {
+                self.chars.nth(1);
+                TokenKind::Ellipsis
+            }
+            Some('(') => TokenKind::LeftParen,
+            Some(')') => TokenKind::RightParen,
+            Some('=') => TokenKind::Equal,
+            Some('|') if self.chars.clone().next() == Some('}') => {
+                self.chars.next();
+                TokenKind::RightBraceBar
+            }
             Some('|') => TokenKind::VerticalBar,
             Some('\'') => {
                 let mut string = String::new();
@@ -71,7 +95,7 @@ impl<'a> Lexer<'a> {
                         }
                     }
                 };
-                TokenKind::Identifier(string)
+                TokenKind::String(string)
             },
             Some('"') => {
                 let mut string = String::new();
@@ -102,19 +126,64 @@ impl<'a> Lexer<'a> {
                         }
                     }
                 };
-                TokenKind::Identifier(string)
+                TokenKind::String(string)
             },
-            Some(char) if self.is_identifier(char) => {
-                let mut identifier = char.to_string();
+            // A regex such as `/^u[0-9]+$/`, in which `\/` is a slash
+            Some('/') => {
+                let mut pattern = String::new();
                 loop {
-                    match self.chars.clone().next() {
-                        Some(c) if self.is_identifier(c) => {
-                            identifier.push(c);
+                    match self.chars.next() {
+                        Some('/') => break,
+                        Some('\\') if self.chars.clone().next() == Some('/') => {
+                            pattern.push('/');
                             self.chars.next();
                         }
-                        _ => break,
+                        Some(char) => pattern.push(char),
+                        None => {
+                            return Err(SchemaError::UnterminatedString {
+                                location: Location { start, end: self.offset() },
+                            });
+                        }
                     }
                 }
+                TokenKind::Regex(pattern)
+            },
+            Some(char) if char.is_ascii_digit() || char == '-' => {
+                let mut number = char.to_string();
+                self.read_digits(&mut number);
+                if self.peek_number_part(&['.']) {
+                    number.push(self.chars.next().unwrap());
+                    self.read_digits(&mut number);
+                }
+                if self.peek_number_part(&['e', 'E']) {
+                    number.push(self.chars.next().unwrap());
+                    if let Some(sign @ ('+' | '-')) = self.chars.clone().next() {
+                        number.push(sign);
+                        self.chars.next();
+                    }
+                    self.read_digits(&mut number);
+                }
+
+                if number == "-" {
+                    return Err(SchemaError::UnexpectedCharacter {
+                        text: number,
+                        location: Location { start, end: self.offset() },
+                    });
+                }
+
+                // A key such as `1st` starts with digits but is an identifier
+                match self.chars.clone().next() {
+                    Some(c) if char != '-' && self.is_identifier(c) => {
+                        let mut identifier = number;
+                        self.read_identifier(&mut identifier);
+                        TokenKind::Identifier(identifier)
+                    }
+                    _ => TokenKind::Number(number),
+                }
+            },
+            Some(char) if self.is_identifier(char) => {
+                let mut identifier = char.to_string();
+                self.read_identifier(&mut identifier);
                 TokenKind::Identifier(identifier)
             },
             Some(' ') => return self.read_next_token(),
@@ -123,10 +192,11 @@ impl<'a> Lexer<'a> {
                 text: char.to_string(),
                 location: Location { start, end: self.offset() },
             }),
-            None => TokenKind::EOF,
+            None => TokenKind::Eof,
         };
 
         let end = self.offset();
+        self.after_equal = kind == TokenKind::Equal;
 
         Ok(Token {
             kind,
@@ -135,6 +205,39 @@ impl<'a> Lexer<'a> {
         })
     }
 
+    fn read_digits(&mut self, text: &mut String) {
+        while let Some(c) = self.chars.clone().next().filter(|c| c.is_ascii_digit()) {
+            text.push(c);
+            self.chars.next();
+        }
+    }
+
+    /// Read the rest of an identifier. In a value after `=`, a hyphen
+    /// between identifier characters belongs to it, as in `date-time`.
+    fn read_identifier(&mut self, text: &mut String) {
+        loop {
+            let mut chars = self.chars.clone();
+            match (chars.next(), chars.next()) {
+                (Some(c), _) if self.is_identifier(c) => text.push(c),
+                (Some('-'), Some(c)) if self.after_equal && self.is_identifier(c) => text.push('-'),
+                _ => break,
+            }
+            self.chars.next();
+        }
+    }
+
+    /// Whether the next character is one of `marks` followed by a digit or
+    /// an exponent sign, as in `1.5` or `1e-3`.
+    fn peek_number_part(&self, marks: &[char]) -> bool {
+        let mut chars = self.chars.clone();
+        match (chars.next(), chars.next()) {
+            (Some(mark), Some(next)) if marks.contains(&mark) => {
+                next.is_ascii_digit() || (mark != '.' && (next == '+' || next == '-'))
+            }
+            _ => false,
+        }
+    }
+
     fn offset(&self) -> usize {
         self.source.len() - self.chars.as_str().len()
     }
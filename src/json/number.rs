@@ -0,0 +1,127 @@
+use std::cmp::Ordering;
+use serde_json::Number;
+
+/// How an integer must fit in a machine integer, for consumers which store
+/// it as one.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Fit {
+    I64,
+    U64,
+}
+
+/// Constraints of `number`, `integer` and `float`, written as arguments such
+/// as `integer(min=0, max=100, multipleOf=5)` or `integer(u64)`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NumberType {
+    pub min: Option<Number>,
+    pub max: Option<Number>,
+    pub multiple_of: Option<Number>,
+    pub fit: Option<Fit>,
+}
+
+impl NumberType {
+    /// Build the constraints from arguments. `i64` and `u64` apply only to
+    /// integers.
+    pub fn from_args(args: &[&str], integer: bool) -> Option<NumberType> {
+        let mut type_ = NumberType::default();
+        for arg in args {
+            match arg.split_once('=').map(|(k, v)| (k.trim(), v.trim().parse::<Number>().ok())) {
+                Some(("min", Some(min))) => type_.min = Some(min),
+                Some(("max", Some(max))) => type_.max = Some(max),
+                Some(("multipleOf", Some(step))) if step.as_f64().is_some_and(|step| step > 0.0) => {
+                    type_.multiple_of = Some(step)
+                }
+                Some(_) => return None,
+                None => match *arg {
+                    "i64" if integer => type_.fit = Some(Fit::I64),
+                    "u64" if integer => type_.fit = Some(Fit::U64),
+                    _ => return None,
+                },
+            }
+        }
+        match (&type_.min, &type_.max) {
+            (Some(min), Some(max)) if compare(min, max) == Some(Ordering::Greater) => None,
+            _ => Some(type_),
+        }
+    }
+
+    pub fn accepts(&self, number: &Number) -> bool {
+        let fits = match self.fit {
+            Some(Fit::I64) => number.is_i64(),
+            Some(Fit::U64) => number.is_u64(),
+            None => true,
+        };
+        let is_multiple = |step: &Number| match (integer(number), integer(step)) {
+            (Some(value), Some(step)) => value % step == 0,
+            _ => match (number.as_f64(), step.as_f64()) {
+                (Some(value), Some(step)) => {
+                    let quotient = value / step;
+                    (quotient - quotient.round()).abs() < 1e-9
+                }
+                _ => false,
+            },
+        };
+
+        fits
+            && self.min.as_ref().is_none_or(|min| compare(number, min).is_some_and(Ordering::is_ge))
+            && self.max.as_ref().is_none_or(|max| compare(number, max).is_some_and(Ordering::is_le))
+            && self.multiple_of.as_ref().is_none_or(is_multiple)
+    }
+
+    /// Write the type with its constraints, as `integer(min=0)`.
+    pub fn write(&self, f: &mut std::fmt::Formatter, name: &str) -> std::fmt::Result {
+        let mut args = vec![];
+        match self.fit {
+            Some(Fit::I64) => args.push("i64".to_string()),
+            Some(Fit::U64) => args.push("u64".to_string()),
+            None => {}
+        }
+        if let Some(min) = &self.min {
+            args.push(format!("min={}", min));
+        }
+        if let Some(max) = &self.max {
+            args.push(format!("max={}", max));
+        }
+        if let Some(step) = &self.multiple_of {
+            args.push(format!("multipleOf={}", step));
+        }
+
+        match args.is_empty() {
+            true => write!(f, "{}", name),
+            false => write!(f, "{}({})", name, args.join(", ")),
+        }
+    }
+}
+
+/// Compare two numbers, exactly when both are written as integers and as
+/// f64 otherwise.
+pub fn compare(a: &Number, b: &Number) -> Option<Ordering> {
+    match (integer(a), integer(b)) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+    }
+}
+
+/// The number written the same way as any number it compares equal to, as
+/// `1` for `1.0` and `1e0`.
+pub fn canonical(number: &Number) -> Number {
+    let text = match (integer(number), number.as_f64()) {
+        (Some(integer), _) => integer.to_string(),
+        (None, Some(float)) => float.to_string(),
+        (None, None) => return number.clone(),
+    };
+    text.parse().unwrap_or_else(|_| number.clone())
+}
+
+/// Whether the number is written without a fraction or exponent, whatever
+/// its size.
+pub fn is_integer(number: &Number) -> bool {
+    let text = number.to_string();
+    let digits = text.strip_prefix('-').unwrap_or(&text);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// The number as written, if it is an integer.
+fn integer(number: &Number) -> Option<i128> {
+    number.to_string().parse().ok()
+}
@@ -1,15 +1,22 @@
-use super::data::{Token, TokenKind, Value, Type, Object, Property, Array};
+use crate::decimal::DecimalType;
+use super::items::Items;
+use super::number::NumberType;
+use super::string::StringType;
+use super::data::{Token, TokenKind, Location, Value, Type, Object, Property, Array, Element, Tuple, Key, Record, Definitions};
 use super::error::{SchemaErrors, SchemaError};
 use super::lexer::Lexer;
 
 pub struct Parser {
     token: Option<Box<Token>>,
+    /// The names of defined types used in the schema, checked once every
+    /// definition has been read.
+    references: Vec<(String, Location)>,
 }
 
 impl Parser {
     pub fn new(text: &str) -> Result<Parser, SchemaErrors> {
         let mut lexer = Lexer::new(text);
-        Ok(Parser { token: lexer.token()? })
+        Ok(Parser { token: lexer.token()?, references: vec![] })
     }
 
     fn expect(&mut self, kind: TokenKind) -> Result<(), SchemaError> {
@@ -27,7 +34,7 @@ impl Parser {
 
     fn expect_identifier(&mut self) -> Result<String, SchemaError> {
         let token = self.token.take().unwrap();
-        if let TokenKind::Identifier(identifier) = token.kind {
+        if let TokenKind::Identifier(identifier) | TokenKind::Number(identifier) | TokenKind::String(identifier) = token.kind {
             self.token = token.next;
             return Ok(identifier);
         }
@@ -38,23 +45,60 @@ impl Parser {
         })
     }
 
+    /// The value of a named argument: an identifier, number, string or regex.
+    fn expect_arg_value(&mut self) -> Result<String, SchemaError> {
+        if let Some(TokenKind::Regex(pattern)) = self.token.as_ref().map(|token| &token.kind) {
+            let pattern = pattern.clone();
+            self.token = self.token.take().unwrap().next;
+            return Ok(pattern);
+        }
+        self.expect_identifier()
+    }
+
     fn expect_type(&mut self) -> Result<Type, SchemaError> {
         let token = self.token.take().unwrap();
 
-        if let TokenKind::Identifier(identifier) = token.kind {
+        if let TokenKind::String(string) = token.kind {
             self.token = token.next;
-
-            match identifier.as_str() {
-                "null" => Ok(Type::Null),
-                "string" => Ok(Type::String),
-                "number" => Ok(Type::Number),
-                "boolean" => Ok(Type::Boolean),
+            Ok(Type::Literal(serde_json::Value::String(string)))
+        }
+        else if let TokenKind::Number(number) = token.kind {
+            self.token = token.next;
+            match serde_json::from_str(&number) {
+                Ok(number @ serde_json::Value::Number(_)) => Ok(Type::Literal(number)),
                 _ => Err(SchemaError::InvalidType {
-                    type_: identifier,
+                    type_: number,
                     location: token.location,
                 }),
             }
         }
+        else if let TokenKind::Identifier(identifier) = token.kind {
+            self.token = token.next;
+            let args = self.type_args()?;
+            let args = args.iter().map(|arg| arg.as_str()).collect::<Vec<&str>>();
+
+            let type_ = match (identifier.as_str(), args.as_slice()) {
+                ("null", []) => Some(Type::Null),
+                ("string", args) => StringType::from_args(args).map(Type::String),
+                ("number", args) => NumberType::from_args(args, false).map(Type::Number),
+                ("integer", args) => NumberType::from_args(args, true).map(Type::Integer),
+                ("float", args) => NumberType::from_args(args, false).map(Type::Float),
+                ("decimal", args) => DecimalType::from_args(args).map(Type::Decimal),
+                ("boolean", []) => Some(Type::Boolean),
+                ("true", []) => Some(Type::Literal(serde_json::Value::Bool(true))),
+                ("false", []) => Some(Type::Literal(serde_json::Value::Bool(false))),
+                (name, []) => {
+                    let location = Location { start: token.location.start, end: token.location.end };
+                    self.references.push((name.to_string(), location));
+                    Some(Type::Reference(name.to_string()))
+                }
+                _ => None,
+            };
+            type_.ok_or(SchemaError::InvalidType {
+                type_: identifier,
+                location: token.location,
+            })
+        }
         else {
             Err(SchemaError::UnexpectedToken {
                 expected_kind: TokenKind::Identifier("".to_string()),
@@ -64,21 +108,71 @@ impl Parser {
         }
     }
 
-    fn expect_types(&mut self) -> Result<Vec<Type>, SchemaError> {
-        if self.peek(TokenKind::LeftBrace) {
-            let object = self.object()?;
-            return Ok(vec![Type::Object(Box::new(object))]);
+    /// Arguments of a type such as `decimal(10, 2, min=0)`, each as
+    /// `<value>` or `<name>=<value>`.
+    fn type_args(&mut self) -> Result<Vec<String>, SchemaError> {
+        let mut args = vec![];
+        if !self.consume(TokenKind::LeftParen) {
+            return Ok(args);
+        }
+        while !self.consume(TokenKind::RightParen) {
+            let arg = self.expect_identifier()?;
+            if self.consume(TokenKind::Equal) {
+                args.push(format!("{}={}", arg, self.expect_arg_value()?));
+            } else {
+                args.push(arg);
+            }
+            if !self.consume(TokenKind::Comma) {
+                self.expect(TokenKind::RightParen)?;
+                break;
+            }
         }
+        Ok(args)
+    }
+
+    /// Wrap a type in an array for each `[]` after it, as in `number[]`.
+    fn array_suffix(&mut self, mut type_: Type) -> Result<Type, SchemaError> {
+        while self.consume(TokenKind::LeftBracket) {
+            self.expect(TokenKind::RightBracket)?;
+            type_ = Type::Array(Box::new(Array::new(vec![type_], Items::default())));
+        }
+        Ok(type_)
+    }
+
+    /// A single type of a union: an object, array, record, tuple, primitive
+    /// or literal, followed by any `[]`.
+    fn union_member(&mut self) -> Result<Type, SchemaError> {
+        let type_ = if self.peek_object() {
+            Type::Object(Box::new(self.object()?))
+        } else if self.peek(TokenKind::Identifier("Array".to_string())) {
+            Type::Array(Box::new(self.array()?))
+        } else if self.peek(TokenKind::Identifier("Record".to_string())) {
+            Type::Record(Box::new(self.record()?))
+        } else if self.peek(TokenKind::LeftBracket) {
+            Type::Tuple(Box::new(self.tuple()?))
+        } else {
+            self.expect_type()?
+        };
+        self.array_suffix(type_)
+    }
 
-        if self.peek(TokenKind::Identifier("Array".to_string())) {
-            let array = self.array()?;
-            return Ok(vec![Type::Array(Box::new(array))]);
+    /// A type of a union, which may be an intersection such as
+    /// `{id: string} & {name: string}`.
+    fn intersection(&mut self) -> Result<Type, SchemaError> {
+        let mut types = vec![self.union_member()?];
+        while self.consume(TokenKind::Ampersand) {
+            types.push(self.union_member()?);
+        }
+        match types.len() {
+            1 => Ok(types.pop().unwrap()),
+            _ => Ok(Type::Intersection(types)),
         }
+    }
 
-        // Primitive types
+    fn expect_types(&mut self) -> Result<Vec<Type>, SchemaError> {
         let mut types = vec![];
         loop {
-            types.push(self.expect_type()?);
+            types.push(self.intersection()?);
             if !self.consume(TokenKind::VerticalBar) {
                 break;
             }
@@ -103,22 +197,73 @@ impl Parser {
         false
     }
 
-    pub fn parse(&mut self) -> Result<Value, SchemaError> {
-        let value = self.value()?;
-        self.expect(TokenKind::EOF)?;
-        Ok(value)
+    /// The location of the next token.
+    fn location(&self) -> Location {
+        match &self.token {
+            Some(token) => Location { start: token.location.start, end: token.location.end },
+            None => Location { start: 0, end: 0 },
+        }
     }
 
-    fn value(&mut self) -> Result<Value, SchemaError> {
-        if self.peek(TokenKind::LeftBrace) {
-            Ok(Value::Object(self.object()?))
+    fn peek_object(&self) -> bool {
+        self.peek(TokenKind::LeftBrace) || self.peek(TokenKind::LeftBraceBar)
+    }
+
+    pub fn parse(&mut self) -> Result<(Value, Definitions), SchemaError> {
+        let definitions = self.definitions()?;
+        let value = self.value()?;
+        self.expect(TokenKind::Eof)?;
+
+        for (name, location) in self.references.drain(..) {
+            if !definitions.contains_key(&name) {
+                return Err(SchemaError::UndefinedType { name, location });
+            }
         }
-        else if self.peek(TokenKind::Identifier("Array".to_string())) {
-            Ok(Value::Array(self.array()?))
+        if let Some(name) = definitions.keys().find(|name| unguarded(&definitions, name)) {
+            return Err(SchemaError::UnguardedRecursion { name: name.clone() });
         }
-        else {
-            Ok(Value::Types(self.expect_types()?))
+
+        let mut conflicts = vec![];
+        let mut check = |type_: &Type| if let Type::Intersection(types) = type_ {
+            conflicts.extend(conflict(&definitions, types));
+        };
+        definitions.values().flatten().for_each(|type_| type_.walk(&mut check));
+        value.walk(&mut check);
+        if let Some(error) = conflicts.into_iter().next() {
+            return Err(error);
+        }
+
+        Ok((value, definitions))
+    }
+
+    /// Type definitions such as `type Id = string | integer;`, before the
+    /// schema.
+    fn definitions(&mut self) -> Result<Definitions, SchemaError> {
+        let mut definitions = Definitions::new();
+        while self.consume(TokenKind::Identifier("type".to_string())) {
+            let location = self.location();
+            let name = self.expect_identifier()?;
+            if definitions.contains_key(&name) {
+                return Err(SchemaError::DuplicateType { name, location });
+            }
+            self.expect(TokenKind::Equal)?;
+            let types = self.expect_types()?;
+            self.expect(TokenKind::Semicolon)?;
+            definitions.insert(name, types);
         }
+        Ok(definitions)
+    }
+
+    fn value(&mut self) -> Result<Value, SchemaError> {
+        let mut types = self.expect_types()?;
+        if types.len() == 1 {
+            match types.pop().unwrap() {
+                Type::Object(object) => return Ok(Value::Object(*object)),
+                Type::Array(array) => return Ok(Value::Array(*array)),
+                type_ => types.push(type_),
+            }
+        }
+        Ok(Value::Types(types))
     }
 
     fn array(&mut self) -> Result<Array, SchemaError> {
@@ -126,13 +271,101 @@ impl Parser {
         self.expect(TokenKind::LessThan)?;
         let type_ = self.expect_types()?;
         self.expect(TokenKind::GreaterThan)?;
-        Ok(Array::new(type_))
+
+        let location = self.location();
+        let args = self.type_args()?;
+        let args = args.iter().map(|arg| arg.as_str()).collect::<Vec<&str>>();
+        let items = Items::from_args(&args).ok_or(SchemaError::InvalidType {
+            type_: format!("Array({})", args.join(", ")),
+            location,
+        })?;
+        Ok(Array::new(type_, items))
+    }
+
+    fn record(&mut self) -> Result<Record, SchemaError> {
+        self.expect(TokenKind::Identifier("Record".to_string()))?;
+        self.expect(TokenKind::LessThan)?;
+
+        let location = self.location();
+        let key = match self.token.as_ref().map(|token| &token.kind) {
+            Some(TokenKind::Regex(pattern)) => {
+                let pattern = pattern.clone();
+                self.token = self.token.take().unwrap().next;
+                regex::Regex::new(&pattern).ok().map(Key::Pattern).ok_or(pattern)
+            }
+            _ => {
+                let mut types = self.expect_types()?;
+                let literals = types
+                    .iter()
+                    .map(|type_| match type_ {
+                        Type::Literal(serde_json::Value::String(literal)) => Some(literal.clone()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<String>>>();
+                match (types.as_slice(), literals) {
+                    ([Type::String(_)], _) => match types.pop() {
+                        Some(Type::String(string)) => Ok(Key::String(string)),
+                        _ => unreachable!(),
+                    },
+                    (_, Some(literals)) => Ok(Key::Literals(literals)),
+                    _ => Err(types.iter().map(|type_| type_.to_string()).collect::<Vec<String>>().join(" | ")),
+                }
+            }
+        };
+        let key = key.map_err(|key| SchemaError::InvalidRecordKey { key, location })?;
+
+        self.expect(TokenKind::Comma)?;
+        let types = self.expect_types()?;
+        self.expect(TokenKind::GreaterThan)?;
+        Ok(Record::new(key, types))
+    }
+
+    fn tuple(&mut self) -> Result<Tuple, SchemaError> {
+        self.expect(TokenKind::LeftBracket)?;
+
+        let mut elements: Vec<Element> = vec![];
+        let mut rest = None;
+        while !self.consume(TokenKind::RightBracket) {
+            let location = self.location();
+            let invalid = |reason: &str| SchemaError::InvalidTuple {
+                reason: reason.to_string(),
+                location,
+            };
+            if rest.is_some() {
+                return Err(invalid("An element follows the rest element"));
+            }
+
+            if self.consume(TokenKind::Ellipsis) {
+                match self.expect_types()?.pop() {
+                    Some(Type::Array(array)) => rest = Some(array.types),
+                    _ => return Err(invalid("The rest element must be an array, such as ...number[]")),
+                }
+            } else {
+                let types = self.expect_types()?;
+                let optional = self.consume(TokenKind::Question);
+                if !optional && elements.last().is_some_and(|element| element.optional) {
+                    return Err(invalid("A required element follows an optional element"));
+                }
+                elements.push(Element { types, optional });
+            }
+
+            if !self.consume(TokenKind::Comma) {
+                self.expect(TokenKind::RightBracket)?;
+                break;
+            }
+        }
+        Ok(Tuple::new(elements, rest))
     }
 
     fn object(&mut self) -> Result<Object, SchemaError> {
-        let mut object = Object::new();
-        self.expect(TokenKind::LeftBrace)?;
-        while !self.consume(TokenKind::RightBrace) {
+        let closed = self.consume(TokenKind::LeftBraceBar);
+        if !closed {
+            self.expect(TokenKind::LeftBrace)?;
+        }
+        let end = || if closed { TokenKind::RightBraceBar } else { TokenKind::RightBrace };
+
+        let mut object = Object::new(closed);
+        while !self.consume(end()) {
             let property = self.property()?;
             object.properties.push(property);
             self.consume(TokenKind::Comma);
@@ -142,10 +375,69 @@ impl Parser {
 
     fn property(&mut self) -> Result<Property, SchemaError> {
         let name = self.expect_identifier()?;
+        let optional = self.consume(TokenKind::Question);
         self.expect(TokenKind::Colon)?;
 
         let types = self.expect_types()?;
 
-        Ok(Property::new(name, types))
+        Ok(Property::new(name, types, optional))
+    }
+}
+
+/// Whether the type refers back to itself without an object, array, record
+/// or tuple in between, as in `type A = B | null; type B = A;`, which no
+/// value could ever end.
+fn unguarded(definitions: &Definitions, name: &str) -> bool {
+    let mut seen = vec![];
+    let mut pending = vec![name];
+    while let Some(current) = pending.pop() {
+        let members = definitions[current].iter().flat_map(|type_| match type_ {
+            Type::Intersection(types) => types.iter().collect(),
+            type_ => vec![type_],
+        });
+        for type_ in members {
+            let Type::Reference(next) = type_ else { continue };
+            if next == name {
+                return true;
+            }
+            if !seen.contains(&next) {
+                seen.push(next);
+                pending.push(next);
+            }
+        }
+    }
+    false
+}
+
+/// A property which two objects of an intersection declare differently.
+fn conflict(definitions: &Definitions, types: &[Type]) -> Option<SchemaError> {
+    let objects = types
+        .iter()
+        .flat_map(|type_| type_.intersected(definitions))
+        .filter_map(|type_| match type_ {
+            Type::Object(object) => Some(object.as_ref()),
+            _ => None,
+        })
+        .collect::<Vec<&Object>>();
+    for (i, object) in objects.iter().enumerate() {
+        for property in &object.properties {
+            let other = objects[i + 1..]
+                .iter()
+                .flat_map(|other| &other.properties)
+                .find(|other| other.name == property.name && !other.same_as(property));
+            if let Some(other) = other {
+                let describe = |property: &Property| format!(
+                    "{}{}",
+                    if property.optional { "?: " } else { ": " },
+                    property.types.iter().map(|type_| type_.to_string()).collect::<Vec<String>>().join(" | "),
+                );
+                return Some(SchemaError::ConflictingProperty {
+                    name: property.name.clone(),
+                    left: describe(property),
+                    right: describe(other),
+                });
+            }
+        }
     }
+    None
 }
@@ -1,28 +1,75 @@
-use std::io::BufRead;
-
-use super::data::Value;
-use super::error::{ SchemaErrors, ValidationErrors };
+use crate::hygiene::Lines;
+use crate::limits::Limits;
+use super::data::{Definitions, Type, Value};
+use super::error::{ SchemaErrors, ValidationError, ValidationErrors };
 use super::parser::Parser;
 use super::validator::Validator;
 
 #[derive(Debug, PartialEq)]
 pub struct Schema {
     pub root: Value,
+    pub definitions: Definitions,
 }
 
 impl Schema {
     pub fn from_text(text: &str) -> Result<Self, SchemaErrors> {
         let mut parser = Parser::new(text)?;
-        Ok(Schema { root: parser.parse()? })
+        let (root, definitions) = parser.parse()?;
+        Ok(Schema { root, definitions })
+    }
+
+    /// Reject keys which are not declared, in every object of the schema.
+    pub fn close_objects(&mut self) {
+        self.root.close();
+        self.definitions.values_mut().flatten().for_each(Type::close);
     }
 
-    pub fn print_and_validate(&self, rdr: Box<dyn BufRead>) -> Result<(), ValidationErrors> {
-        let text = rdr.lines().collect::<Result<Vec<String>, _>>().unwrap().join("\n");
+    pub fn print_and_validate(&self, lines: &mut Lines, limits: &Limits) -> Result<(), ValidationErrors> {
+        let mut raw = String::new();
+        let mut text = vec![];
+        for line in lines {
+            if line.too_long || raw.len() + line.raw.len() > limits.max_document_size {
+                return Err(ValidationError::DocumentTooLarge { limit: limits.max_document_size }.into());
+            }
+            raw.push_str(&line.raw);
+            text.push(line.text);
+        }
+        let text = text.join("\n");
 
-        println!("{}", text);
+        print!("{}", raw);
 
-        Validator::validate(&self.root, &text)?;
+        if depth(&text) > limits.max_depth {
+            return Err(ValidationError::TooDeep { limit: limits.max_depth }.into());
+        }
+        // Errors past the limit are only counted, at every level
+        if let Err(errs) = Validator::new(&self.definitions, limits.max_errors).validate(&self.root, &text) {
+            let mut errors = ValidationErrors::new(limits.max_errors);
+            errors.extend(errs);
+            return Err(errors);
+        }
 
         Ok(())
     }
 }
+
+/// The deepest nesting of arrays and objects, found without parsing so that
+/// a hostile document cannot exhaust the stack.
+fn depth(text: &str) -> usize {
+    let (mut depth, mut deepest) = (0usize, 0);
+    let (mut in_string, mut escaped) = (false, false);
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '[' | '{' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
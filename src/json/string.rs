@@ -0,0 +1,212 @@
+use regex::Regex;
+
+/// A named format of `string(format=...)`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Format {
+    DateTime,
+    Email,
+    Uuid,
+    Uri,
+    Ipv4,
+    Ipv6,
+}
+
+impl Format {
+    fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "date-time" => Some(Format::DateTime),
+            "email" => Some(Format::Email),
+            "uuid" => Some(Format::Uuid),
+            "uri" => Some(Format::Uri),
+            "ipv4" => Some(Format::Ipv4),
+            "ipv6" => Some(Format::Ipv6),
+            _ => None,
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            Format::DateTime => is_date_time(value),
+            Format::Email => is_email(value),
+            Format::Uuid => {
+                value.len() == 36 && value.char_indices().all(|(i, c)| match i {
+                    8 | 13 | 18 | 23 => c == '-',
+                    _ => c.is_ascii_hexdigit(),
+                })
+            }
+            Format::Uri => is_uri(value),
+            Format::Ipv4 => value.parse::<std::net::Ipv4Addr>().is_ok(),
+            Format::Ipv6 => value.parse::<std::net::Ipv6Addr>().is_ok(),
+        }
+    }
+}
+
+impl std::fmt::Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Format::DateTime => write!(f, "date-time"),
+            Format::Email => write!(f, "email"),
+            Format::Uuid => write!(f, "uuid"),
+            Format::Uri => write!(f, "uri"),
+            Format::Ipv4 => write!(f, "ipv4"),
+            Format::Ipv6 => write!(f, "ipv6"),
+        }
+    }
+}
+
+/// An RFC 3339 date-time such as `2024-02-29T12:00:00Z`.
+fn is_date_time(value: &str) -> bool {
+    let (Some(date), Some(time)) = (value.get(..10), value.get(11..)) else {
+        return false;
+    };
+    if !matches!(value.as_bytes()[10], b'T' | b't' | b' ') {
+        return false;
+    }
+
+    let number = |text: &str| match text.bytes().all(|b| b.is_ascii_digit()) {
+        true => text.parse::<u32>().ok(),
+        false => None,
+    };
+    let parts = date.split('-').map(number).collect::<Vec<Option<u32>>>();
+    let [Some(year), Some(month), Some(day)] = parts.as_slice() else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    if date.len() != 10 || !(1..=days).contains(day) {
+        return false;
+    }
+
+    let (time, offset) = match time.find(['Z', 'z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => return false,
+    };
+    let offset_ok = match offset {
+        "Z" | "z" => true,
+        offset => offset.len() == 6 && match offset[1..].split_once(':') {
+            Some((hour, minute)) => number(hour).is_some_and(|h| h < 24) && number(minute).is_some_and(|m| m < 60),
+            None => false,
+        },
+    };
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+    let parts = time.split(':').map(number).collect::<Vec<Option<u32>>>();
+    let [Some(hour), Some(minute), Some(second)] = parts.as_slice() else {
+        return false;
+    };
+
+    offset_ok
+        && time.len() == 8
+        && *hour < 24
+        && *minute < 60
+        && *second <= 60
+        && fraction.is_none_or(|fraction| !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// An address such as `name@example.com`: a local part and a domain of
+/// dot-separated labels, without spaces.
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+    let label = |label: &str| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    };
+    !local.is_empty()
+        && !local.contains(['@', ' ', '\t'])
+        && domain.contains('.')
+        && domain.split('.').all(label)
+}
+
+/// An absolute URI such as `https://example.com/a?b`: a scheme, a colon and
+/// the rest without spaces.
+fn is_uri(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once(':') else {
+        return false;
+    };
+    scheme.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !rest.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Refinements of `string`, written as arguments such as
+/// `string(min=1, max=64, pattern=/^[a-z]+$/, format=email)`. Lengths count
+/// characters.
+#[derive(Debug, Clone, Default)]
+pub struct StringType {
+    pub pattern: Option<Regex>,
+    pub min: Option<usize>,
+    pub max: Option<usize>,
+    pub format: Option<Format>,
+}
+
+impl PartialEq for StringType {
+    fn eq(&self, other: &StringType) -> bool {
+        self.pattern.as_ref().map(Regex::as_str) == other.pattern.as_ref().map(Regex::as_str)
+            && self.min == other.min
+            && self.max == other.max
+            && self.format == other.format
+    }
+}
+
+impl StringType {
+    pub fn from_args(args: &[&str]) -> Option<StringType> {
+        let mut type_ = StringType::default();
+        for arg in args {
+            match arg.split_once('=').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("pattern", pattern)) => type_.pattern = Some(Regex::new(pattern).ok()?),
+                Some(("min", min)) => type_.min = Some(min.parse().ok()?),
+                Some(("max", max)) => type_.max = Some(max.parse().ok()?),
+                Some(("format", format)) => type_.format = Some(Format::from_name(format)?),
+                _ => return None,
+            }
+        }
+        match (type_.min, type_.max) {
+            (Some(min), Some(max)) if min > max => None,
+            _ => Some(type_),
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        let length = value.chars().count();
+        self.min.is_none_or(|min| length >= min)
+            && self.max.is_none_or(|max| length <= max)
+            && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(value))
+            && self.format.is_none_or(|format| format.accepts(value))
+    }
+}
+
+impl std::fmt::Display for StringType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut args = vec![];
+        if let Some(min) = self.min {
+            args.push(format!("min={}", min));
+        }
+        if let Some(max) = self.max {
+            args.push(format!("max={}", max));
+        }
+        if let Some(pattern) = &self.pattern {
+            args.push(format!("pattern=/{}/", pattern.as_str()));
+        }
+        if let Some(format) = self.format {
+            args.push(format!("format={}", format));
+        }
+
+        match args.is_empty() {
+            true => write!(f, "string"),
+            false => write!(f, "string({})", args.join(", ")),
+        }
+    }
+}
@@ -1,58 +1,67 @@
-use serde_json;
+use std::cmp::Ordering;
 use serde_json::{Map, Value};
 
 use super::data as schema;
 use super::error::{ValidationError, ValidationErrors};
+use super::number;
 
-#[allow(dead_code)]
-pub struct Validator;
+/// An object branch of a union, with the properties of all its objects.
+struct Branch<'b> {
+    type_: &'b schema::Type,
+    properties: Vec<&'b schema::Property>,
+}
+
+pub struct Validator<'a> {
+    definitions: &'a schema::Definitions,
+    max_errors: usize,
+}
 
-impl Validator {
-    pub fn validate(schema: &schema::Value, text: &str) -> Result<bool, ValidationErrors> {
+impl<'a> Validator<'a> {
+    pub fn new(definitions: &'a schema::Definitions, max_errors: usize) -> Validator<'a> {
+        Validator { definitions, max_errors }
+    }
+
+    pub fn validate(&self, schema: &schema::Value, text: &str) -> Result<bool, ValidationErrors> {
         let value: serde_json::Value = Self::parse_json(text)?;
-        Self::value(schema, &value)
+        self.value(schema, &value, "$")
     }
 
     fn parse_json(text: &str) -> Result<Value, ValidationError> {
         Ok(serde_json::from_str(text)?)
     }
 
-    fn value(schema: &schema::Value, value: &Value) -> Result<bool, ValidationErrors> {
+    fn value(&self, schema: &schema::Value, value: &Value, path: &str) -> Result<bool, ValidationErrors> {
         match schema {
             schema::Value::Object(schema) => match value {
-                Value::Object(object) => Self::object(schema, object),
-                _ => Err(
-                    ValidationError::DataTypeMismatch {
-                        types: "object".to_string(),
-                        value: value.to_string(),
-                    }.into()
-                ),
+                Value::Object(object) => self.object(schema, object, path),
+                _ => Err(Self::type_mismatch("object", value, path).into()),
             },
             schema::Value::Array(schema) => match value {
-                Value::Array(array) => Self::array(schema, array),
-                _ => Err(
-                    ValidationError::DataTypeMismatch {
-                        types: "array".to_string(),
-                        value: value.to_string(),
-                    }.into()
-                ),
+                Value::Array(array) => self.array(schema, array, path),
+                _ => Err(Self::type_mismatch("array", value, path).into()),
             },
-            schema::Value::Types(schema) => Self::types(&schema, value),
+            schema::Value::Types(schema) => self.types(schema, value, path),
         }
     }
 
-    fn object(schema: &schema::Object, object: &Map<String, Value>) -> Result<bool, ValidationErrors> {
-        let mut errors = ValidationErrors::new();
+    fn object(&self, schema: &schema::Object, object: &Map<String, Value>, path: &str) -> Result<bool, ValidationErrors> {
+        let properties = schema.properties.iter().collect::<Vec<&schema::Property>>();
+        self.properties(&properties, schema.closed, object, path)
+    }
+
+    fn properties(&self, properties: &[&schema::Property], closed: bool, object: &Map<String, Value>, path: &str) -> Result<bool, ValidationErrors> {
+        let mut errors = ValidationErrors::new(self.max_errors);
 
-        for property in &schema.properties {
+        for property in properties {
             match object.get(&property.name) {
                 Some(value) => {
-                    match Self::property(&property, value) {
+                    match self.property(property, value, &Self::key_path(path, &property.name)) {
                         Ok(_) => {},
                         Err(errs) => errors.extend(errs),
                     }
                 },
-                None => errors.0.push(
+                None if property.optional => {},
+                None => errors.push(
                     ValidationError::PropertyNotFound {
                         name: property.name.clone(),
                     }
@@ -60,6 +69,16 @@ impl Validator {
             }
         }
 
+        if closed {
+            for key in object.keys() {
+                if !properties.iter().any(|property| &property.name == key) {
+                    errors.push(ValidationError::UnexpectedProperty {
+                        path: Self::key_path(path, key),
+                    });
+                }
+            }
+        }
+
         if errors.is_empty() {
             Ok(true)
         } else {
@@ -67,11 +86,27 @@ impl Validator {
         }
     }
 
-    fn array(schema: &schema::Array, array: &Vec<Value>) -> Result<bool, ValidationErrors> {
-        let mut errors = ValidationErrors::new();
+    fn array(&self, schema: &schema::Array, array: &[Value], path: &str) -> Result<bool, ValidationErrors> {
+        let mut errors = ValidationErrors::new(self.max_errors);
+
+        if let (false, Some(expected)) = (schema.items.accepts_length(array.len()), schema.items.length()) {
+            errors.push(ValidationError::ArrayLengthMismatch {
+                path: path.to_string(),
+                expected,
+                found: array.len(),
+            });
+        }
+        for (indices, value) in schema.items.duplicates(array) {
+            errors.push(ValidationError::DuplicateItems {
+                path: path.to_string(),
+                indices: indices.iter().map(|i| i.to_string()).collect::<Vec<String>>().join(", "),
+                key: schema.items.unique.as_ref().map(|unique| unique.to_string()).unwrap_or_default(),
+                value,
+            });
+        }
 
-        for value in array {
-            match Self::types(&schema.types, value) {
+        for (i, value) in array.iter().enumerate() {
+            match self.types(&schema.types, value, &format!("{}[{}]", path, i)) {
             Ok(_) => {},
             Err(errs) => errors.extend(errs),
             }
@@ -84,27 +119,148 @@ impl Validator {
         }
     }
 
-    fn property(schema: &schema::Property, value: &Value) -> Result<bool, ValidationErrors> {
-        Self::types(&schema.types, value)
+    fn record(&self, schema: &schema::Record, object: &Map<String, Value>, path: &str) -> Result<bool, ValidationErrors> {
+        let mut errors = ValidationErrors::new(self.max_errors);
+
+        for (key, value) in object {
+            let path = Self::key_path(path, key);
+            if !schema.key.accepts(key) {
+                errors.push(ValidationError::UnexpectedKey {
+                    path,
+                    expected: schema.key.to_string(),
+                });
+                continue;
+            }
+            if let Err(errs) = self.types(&schema.types, value, &path) {
+                errors.extend(errs);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(true)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validate a value against every type of an intersection. Its objects
+    /// are checked as one object with the properties of all, which is closed
+    /// if any of them is.
+    fn intersection(&self, schema: &[schema::Type], value: &Value, path: &str) -> Result<bool, ValidationErrors> {
+        let mut errors = ValidationErrors::new(self.max_errors);
+
+        let members = schema
+            .iter()
+            .flat_map(|type_| type_.intersected(self.definitions))
+            .collect::<Vec<&schema::Type>>();
+        let objects = members
+            .iter()
+            .filter_map(|type_| match type_ {
+                schema::Type::Object(object) => Some(object.as_ref()),
+                _ => None,
+            })
+            .collect::<Vec<&schema::Object>>();
+        if !objects.is_empty() {
+            let mut properties: Vec<&schema::Property> = vec![];
+            for property in objects.iter().flat_map(|object| &object.properties) {
+                if !properties.iter().any(|other| other.name == property.name) {
+                    properties.push(property);
+                }
+            }
+            let closed = objects.iter().any(|object| object.closed);
+            let result = match value {
+                Value::Object(object) => self.properties(&properties, closed, object, path),
+                _ => Err(Self::type_mismatch("object", value, path).into()),
+            };
+            if let Err(errs) = result {
+                errors.extend(errs);
+            }
+        }
+
+        for type_ in members.into_iter().filter(|type_| !matches!(type_, schema::Type::Object(_))) {
+            if let Err(errs) = self.type_(type_, value, path) {
+                errors.extend(errs);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(true)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn tuple(&self, schema: &schema::Tuple, array: &[Value], path: &str) -> Result<bool, ValidationErrors> {
+        let mut errors = ValidationErrors::new(self.max_errors);
+
+        let required = schema.elements.iter().filter(|element| !element.optional).count();
+        if array.len() < required || (schema.rest.is_none() && array.len() > schema.elements.len()) {
+            errors.push(ValidationError::TupleLengthMismatch {
+                path: path.to_string(),
+                expected: schema.arity(),
+                found: array.len(),
+            });
+        }
+
+        // Elements beyond those declared are checked only against the rest
+        for (i, value) in array.iter().enumerate() {
+            let types = match schema.elements.get(i) {
+                Some(element) => &element.types,
+                None => match &schema.rest {
+                    Some(rest) => rest,
+                    None => break,
+                },
+            };
+            if let Err(errs) = self.types(types, value, &format!("{}[{}]", path, i)) {
+                errors.extend(errs);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(true)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn property(&self, schema: &schema::Property, value: &Value, path: &str) -> Result<bool, ValidationErrors> {
+        self.types(&schema.types, value, path)
     }
 
-    fn types(schema: &Vec<schema::Type>, value: &Value) -> Result<bool, ValidationErrors> {
+    fn types(&self, schema: &Vec<schema::Type>, value: &Value, path: &str) -> Result<bool, ValidationErrors> {
+        // A single type reports its own errors, such as those of nested
+        // properties
+        if let [type_] = schema.as_slice() {
+            return self.type_(type_, value, path);
+        }
+
         let mut ok = false;
         for type_ in schema {
-            match Self::type_(type_, value) {
-            Ok(_) => {
+            if self.type_(type_, value, path).is_ok() {
                 ok = true;
                 break;
-            },
-            Err(_) => {},
             }
         }
 
+        if let (false, Value::Object(object)) = (ok, value) {
+            if let Some(result) = self.discriminated(schema, object, value, path) {
+                return result;
+            }
+        }
+
+        // When a single branch is an object or array like the value, as in
+        // `{id: string} | null`, its errors say more than the mismatch
+        let mut containers = schema.iter().filter(|type_| self.same_container(type_, value));
+        if let (false, Some(type_), None) = (ok, containers.next(), containers.next()) {
+            return self.type_(type_, value, path);
+        }
+
         if ok {
             Ok(true)
         } else {
             Err(
             ValidationError::DataTypeMismatch {
+                path: path.to_string(),
                 types: schema.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(" or "),
                 value: value.to_string(),
             }.into()
@@ -113,37 +269,172 @@ impl Validator {
             
     }
 
-    fn type_ (schema: &schema::Type, value: &Value) -> Result<bool, ValidationErrors> {
+    fn type_ (&self, schema: &schema::Type, value: &Value, path: &str) -> Result<bool, ValidationErrors> {
         match schema {
             schema::Type::Null => match value {
                 Value::Null => Ok(true),
-                _ => Err(Self::type_mismatch("null", value).into())
+                _ => Err(Self::type_mismatch("null", value, path).into())
+            },
+            schema::Type::String(string) => match value {
+                Value::String(text) if string.accepts(text) => Ok(true),
+                _ => Err(Self::type_mismatch(&string.to_string(), value, path).into())
+            },
+            // Like in tsv, a float accepts integers
+            schema::Type::Number(number) | schema::Type::Float(number) => match value {
+                Value::Number(value) if number.accepts(value) => Ok(true),
+                _ => Err(Self::type_mismatch(&schema.to_string(), value, path).into())
             },
-            schema::Type::String => match value {
-                Value::String(_) => Ok(true),
-                _ => Err(Self::type_mismatch("string", value).into())
+            // An integer is written without a fraction or exponent
+            schema::Type::Integer(number) => match value {
+                Value::Number(value) if number::is_integer(value) && number.accepts(value) => Ok(true),
+                _ => Err(Self::type_mismatch(&schema.to_string(), value, path).into())
             },
-            schema::Type::Number => match value {
-                Value::Number(_) => Ok(true),
-                _ => Err(Self::type_mismatch("number", value).into())
+            schema::Type::Decimal(decimal) => match value {
+                Value::String(text) if decimal.accepts(text) => Ok(true),
+                // With `arbitrary_precision`, the number is kept as written
+                Value::Number(number) if decimal.accepts(&number.to_string()) => Ok(true),
+                _ => Err(Self::type_mismatch(&decimal.to_string(), value, path).into())
             },
             schema::Type::Boolean => match value {
                 Value::Bool(_) => Ok(true),
-                _ => Err(Self::type_mismatch("boolean", value).into())
+                _ => Err(Self::type_mismatch("boolean", value, path).into())
+            },
+            schema::Type::Literal(literal) => match Self::literal_matches(literal, value) {
+                true => Ok(true),
+                false => Err(Self::type_mismatch(&literal.to_string(), value, path).into())
             },
             schema::Type::Object(schema) => match value {
-                Value::Object(object) => Ok(Self::object(schema, object)?),
-                _ => Err(Self::type_mismatch("object", value).into())
+                Value::Object(object) => Ok(self.object(schema, object, path)?),
+                _ => Err(Self::type_mismatch("object", value, path).into())
             },
             schema::Type::Array(schema) => match value {
-                Value::Array(array) => Ok(Self::array(schema, array)?),
-                _ => Err(Self::type_mismatch("array", value).into())
+                Value::Array(array) => Ok(self.array(schema, array, path)?),
+                _ => Err(Self::type_mismatch("array", value, path).into())
+            },
+            schema::Type::Record(schema) => match value {
+                Value::Object(object) => Ok(self.record(schema, object, path)?),
+                _ => Err(Self::type_mismatch("object", value, path).into())
+            },
+            schema::Type::Reference(name) => self.types(&self.definitions[name], value, path),
+            schema::Type::Intersection(schema) => self.intersection(schema, value, path),
+            schema::Type::Tuple(schema) => match value {
+                Value::Array(array) => Ok(self.tuple(schema, array, path)?),
+                _ => Err(Self::type_mismatch(&schema.to_string(), value, path).into())
             },
         }
     }
 
-    fn type_mismatch(types: &str, value: &Value) -> ValidationError {
+    /// Validate an object against the branch of a discriminated union, such
+    /// as `{type: "a", ...} | {type: "b", ...}`, selected by its tag. `None`
+    /// if the union has no discriminant or the object has no tag.
+    fn discriminated(&self, schema: &[schema::Type], object: &Map<String, Value>, value: &Value, path: &str) -> Option<Result<bool, ValidationErrors>> {
+        let branches = self.branches(schema);
+        let tag = Self::discriminant(&branches)?;
+        let found = object.get(tag)?;
+
+        let selected = branches.iter().find(|branch| {
+            Self::tags(branch, tag).is_some_and(|tags| tags.iter().any(|literal| Self::literal_matches(literal, found)))
+        });
+        Some(match selected {
+            Some(branch) => self.type_(branch.type_, value, path),
+            None => Err(ValidationError::UnknownDiscriminant {
+                path: Self::key_path(path, tag),
+                expected: branches
+                    .iter()
+                    .filter_map(|branch| Self::tags(branch, tag))
+                    .flatten()
+                    .map(|literal| literal.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" or "),
+                value: found.to_string(),
+            }.into()),
+        })
+    }
+
+    /// The object branches of a union, looking through defined types. An
+    /// intersection such as `Base & {type: "a"}` is a branch with the
+    /// properties of all its objects.
+    fn branches<'b>(&'b self, schema: &'b [schema::Type]) -> Vec<Branch<'b>> {
+        schema.iter().flat_map(|type_| match type_ {
+            schema::Type::Reference(name) => self.branches(&self.definitions[name]),
+            type_ => {
+                let properties = type_
+                    .intersected(self.definitions)
+                    .into_iter()
+                    .filter_map(|type_| match type_ {
+                        schema::Type::Object(object) => Some(&object.properties),
+                        _ => None,
+                    })
+                    .flatten()
+                    .collect::<Vec<&schema::Property>>();
+                match properties.is_empty() && !matches!(type_, schema::Type::Object(_)) {
+                    true => vec![],
+                    false => vec![Branch { type_, properties }],
+                }
+            }
+        }).collect()
+    }
+
+    /// A property which every branch of the union requires, with literal
+    /// types no other branch has.
+    fn discriminant<'b>(branches: &[Branch<'b>]) -> Option<&'b str> {
+        let first = branches.first().filter(|_| branches.len() > 1)?;
+        first.properties.iter().map(|property| property.name.as_str()).find(|name| {
+            let mut seen: Vec<&Value> = vec![];
+            branches.iter().all(|branch| match Self::tags(branch, name) {
+                Some(tags) => tags.into_iter().all(|tag| {
+                    let unique = !seen.iter().any(|other| Self::literal_matches(other, tag));
+                    seen.push(tag);
+                    unique
+                }),
+                None => false,
+            })
+        })
+    }
+
+    /// The literals of a required property whose types are all literals.
+    fn tags<'b>(branch: &Branch<'b>, name: &str) -> Option<Vec<&'b Value>> {
+        let property = branch.properties.iter().find(|property| property.name == name && !property.optional)?;
+        property.types.iter().map(|type_| match type_ {
+            schema::Type::Literal(literal) => Some(literal),
+            _ => None,
+        }).collect()
+    }
+
+    fn literal_matches(literal: &Value, value: &Value) -> bool {
+        match (literal, value) {
+            (Value::Number(literal), Value::Number(number)) => number::compare(literal, number) == Some(Ordering::Equal),
+            (literal, value) => literal == value,
+        }
+    }
+
+    /// Whether the type is an object or array type and the value is of the
+    /// same kind.
+    fn same_container(&self, schema: &schema::Type, value: &Value) -> bool {
+        match (schema, value) {
+            (schema::Type::Reference(name), _) => self.definitions[name]
+                .iter()
+                .any(|type_| self.same_container(type_, value)),
+            (schema::Type::Object(_) | schema::Type::Record(_), Value::Object(_)) => true,
+            (schema::Type::Array(_) | schema::Type::Tuple(_), Value::Array(_)) => true,
+            (schema::Type::Intersection(types), _) => types.iter().any(|type_| self.same_container(type_, value)),
+            _ => false,
+        }
+    }
+
+    /// The path of a property, as `$.name` or `$["key with spaces"]`.
+    fn key_path(path: &str, key: &str) -> String {
+        let is_identifier = key.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+        match is_identifier {
+            true => format!("{}.{}", path, key),
+            false => format!("{}[{}]", path, Value::String(key.to_string())),
+        }
+    }
+
+    fn type_mismatch(types: &str, value: &Value, path: &str) -> ValidationError {
         ValidationError::DataTypeMismatch {
+            path: path.to_string(),
             types: types.to_string(),
             value: value.to_string(),
         }
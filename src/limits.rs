@@ -0,0 +1,15 @@
+/// Bounds on the resources spent on an input, so that a huge line or a
+/// deeply nested document fails validation instead of exhausting memory.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Limits {
+    /// Bytes of a single line, not counting the line ending.
+    pub max_line_length: usize,
+    /// Fields of a single TSV line.
+    pub max_fields: usize,
+    /// Bytes of a whole JSON document.
+    pub max_document_size: usize,
+    /// Nesting of arrays and objects in a JSON document.
+    pub max_depth: usize,
+    /// Errors kept and reported. Errors beyond it are only counted.
+    pub max_errors: usize,
+}
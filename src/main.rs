@@ -6,6 +6,9 @@ mod tsv;
 mod json;
 mod error;
 mod result;
+mod decimal;
+mod hygiene;
+mod limits;
 
 use clap::Parser;
 use cli::Cli;
@@ -14,15 +17,18 @@ use result::Result;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let schema = Schema::from_text(cli.schema_type.to_string().as_str(), cli.schema.as_str())?;
+    let mut schema = Schema::from_text(cli.schema_type.to_string().as_str(), cli.schema.as_str())?;
+    if cli.no_extra_properties {
+        schema.close_objects();
+    }
 
     // reader from file or stdin
-    let reader: Box<dyn BufRead> = match cli.file {
+    let reader: Box<dyn BufRead> = match &cli.file {
         Some(file_name) => Box::new(BufReader::new(std::fs::File::open(file_name).unwrap())),
         None => Box::new(BufReader::new(stdin())),
     };
 
-    if let Err(e) = schema.print_and_validate(reader) {
+    if let Err(e) = schema.print_and_validate(reader, &cli.hygiene(), &cli.limits()) {
         eprintln!("{}", e);
         std::process::exit(1);
     };
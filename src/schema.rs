@@ -2,6 +2,8 @@ use thiserror::Error;
 use std::io::BufRead;
 use crate::tsv;
 use crate::json;
+use crate::hygiene::{Hygiene, HygieneErrors, Lines};
+use crate::limits::Limits;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum SchemaError {
@@ -19,6 +21,18 @@ pub enum ValidationError {
 
     #[error(transparent)]
     Json(#[from] json::ValidationErrors),
+
+    #[error("{}Error: Invalid UTF-8 at line {line}", .validation.as_ref().map(|error| error.to_string()).unwrap_or_default())]
+    InvalidUtf8 {
+        line: usize,
+        validation: Option<Box<ValidationError>>,
+    },
+
+    #[error("{}{hygiene}", .validation.as_ref().map(|error| error.to_string()).unwrap_or_default())]
+    Hygiene {
+        hygiene: HygieneErrors,
+        validation: Option<Box<ValidationError>>,
+    },
 }
 
 pub enum Schema {
@@ -34,10 +48,41 @@ impl Schema {
             _ => panic!("Unknown schema type: {}", schema_type),
         }
     }
-    pub fn print_and_validate(&self, reader: Box<dyn BufRead>) -> Result<(), ValidationError> {
+    /// Apply `--no-extra-properties`, which only concerns json schemas.
+    pub fn close_objects(&mut self) {
         match self {
-            Schema::Tsv(schema) => Ok(schema.print_and_validate(reader)?),
-            Schema::Json(schema) => Ok(schema.print_and_validate(reader)?),
+            Schema::Tsv(_) => {}
+            Schema::Json(schema) => schema.close_objects(),
+        }
+    }
+
+    pub fn print_and_validate(&self, reader: Box<dyn BufRead>, hygiene: &Hygiene, limits: &Limits) -> Result<(), ValidationError> {
+        let max_line_length = match self {
+            Schema::Tsv(_) => limits.max_line_length,
+            // A line of a document is bounded by the size of the document
+            Schema::Json(_) => limits.max_document_size,
+        };
+        let mut lines = Lines::new(reader, max_line_length);
+        let result = match self {
+            Schema::Tsv(schema) => schema.print_and_validate(&mut lines, limits).map_err(ValidationError::from),
+            Schema::Json(schema) => schema.print_and_validate(&mut lines, limits).map_err(ValidationError::from),
+        };
+
+        let result = match hygiene.check(&lines) {
+            Ok(()) => result,
+            Err(errors) => Err(ValidationError::Hygiene {
+                hygiene: errors,
+                validation: result.err().map(Box::new),
+            }),
+        };
+
+        // Reading stopped at the invalid line, after the errors before it
+        match lines.invalid_utf8 {
+            Some(line) => Err(ValidationError::InvalidUtf8 {
+                line,
+                validation: result.err().map(Box::new),
+            }),
+            None => result,
         }
     }
 }
@@ -1,5 +1,13 @@
 mod schema;
 mod term;
+mod record;
+mod directive;
+mod expr;
+mod dataset;
+mod syntax;
+mod unit;
+mod charclass;
+mod checkdigit;
 mod error;
 
 pub use schema::Schema;
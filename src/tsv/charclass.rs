@@ -0,0 +1,95 @@
+use unicode_script::{Script, UnicodeScript};
+
+/// A string type restricting which characters may appear in the value.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CharClass {
+    Ascii,
+    Alnum,
+    Hiragana,
+    Katakana,
+    Kanji,
+    Halfwidth,
+    Fullwidth,
+    Script(Script),
+}
+
+impl CharClass {
+    /// A class by type name, or a Unicode script by its full or short name
+    /// such as `greek` or `grek`. Names are matched case-insensitively.
+    pub fn from_name(name: &str) -> Option<CharClass> {
+        match name {
+            "ascii" => Some(CharClass::Ascii),
+            "alnum" => Some(CharClass::Alnum),
+            "hiragana" => Some(CharClass::Hiragana),
+            "katakana" => Some(CharClass::Katakana),
+            "kanji" => Some(CharClass::Kanji),
+            "halfwidth" => Some(CharClass::Halfwidth),
+            "fullwidth" => Some(CharClass::Fullwidth),
+            name => {
+                let name = capitalize(name);
+                Script::from_full_name(&name)
+                    .or_else(|| Script::from_short_name(&name))
+                    .filter(|script| !matches!(script, Script::Common | Script::Inherited | Script::Unknown))
+                    .map(CharClass::Script)
+            }
+        }
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        match self {
+            CharClass::Ascii => c.is_ascii(),
+            CharClass::Alnum => c.is_ascii_alphanumeric(),
+            // The prolonged sound mark and middle dot are written in hiragana
+            // readings too
+            CharClass::Hiragana => matches!(c, '\u{3041}'..='\u{309F}' | 'ー' | '・'),
+            CharClass::Katakana => matches!(c, '\u{30A0}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}'),
+            CharClass::Kanji => matches!(c,
+                '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{F900}'..='\u{FAFF}'
+                | '\u{20000}'..='\u{3134F}' | '々' | '〆' | '〇'),
+            CharClass::Halfwidth => matches!(c, ' '..='~' | '\u{FF61}'..='\u{FFDC}' | '\u{FFE8}'..='\u{FFEE}'),
+            CharClass::Fullwidth => matches!(c,
+                '\u{1100}'..='\u{115F}' | '\u{2E80}'..='\u{303E}' | '\u{3041}'..='\u{33FF}'
+                | '\u{3400}'..='\u{4DBF}' | '\u{4E00}'..='\u{9FFF}' | '\u{A000}'..='\u{A4CF}'
+                | '\u{AC00}'..='\u{D7A3}' | '\u{F900}'..='\u{FAFF}' | '\u{FE30}'..='\u{FE4F}'
+                | '\u{FF01}'..='\u{FF60}' | '\u{FFE0}'..='\u{FFE6}' | '\u{20000}'..='\u{3FFFD}'),
+            // Characters shared between scripts, such as `・` in Japanese,
+            // count for each script they are used with.
+            CharClass::Script(script) => c.script_extension().contains_script(*script),
+        }
+    }
+
+    /// The 1-based position and the character of the first one outside the
+    /// class.
+    pub fn first_offending(&self, value: &str) -> Option<(usize, char)> {
+        value.chars().enumerate().find(|(_, c)| !self.contains(*c)).map(|(i, c)| (i + 1, c))
+    }
+}
+
+/// `old_italic` to `Old_Italic`, the form script names are written in.
+fn capitalize(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("_")
+}
+
+impl std::fmt::Display for CharClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CharClass::Ascii => write!(f, "ascii"),
+            CharClass::Alnum => write!(f, "alnum"),
+            CharClass::Hiragana => write!(f, "hiragana"),
+            CharClass::Katakana => write!(f, "katakana"),
+            CharClass::Kanji => write!(f, "kanji"),
+            CharClass::Halfwidth => write!(f, "halfwidth"),
+            CharClass::Fullwidth => write!(f, "fullwidth"),
+            CharClass::Script(script) => write!(f, "{}", script.full_name().to_lowercase()),
+        }
+    }
+}
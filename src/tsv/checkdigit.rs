@@ -0,0 +1,124 @@
+/// An identifier whose last digits are computed from the others, so that
+/// typos are caught.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CheckDigit {
+    Luhn,
+    Isbn10,
+    Isbn13,
+    Ean,
+    Iban,
+    CorporateNumber,
+}
+
+impl CheckDigit {
+    pub fn from_name(name: &str) -> Option<CheckDigit> {
+        match name {
+            "luhn" => Some(CheckDigit::Luhn),
+            "isbn10" => Some(CheckDigit::Isbn10),
+            "isbn13" => Some(CheckDigit::Isbn13),
+            "ean" | "jan" => Some(CheckDigit::Ean),
+            "iban" => Some(CheckDigit::Iban),
+            "corporate_number" => Some(CheckDigit::CorporateNumber),
+            _ => None,
+        }
+    }
+
+    /// The expected and the written check digits, or `None` if the value is
+    /// not in the format of the identifier.
+    pub fn check(&self, value: &str) -> Option<(String, String)> {
+        // Card numbers and ISBNs are often written in groups
+        let value = match self {
+            _ if value.starts_with('-') || value.ends_with('-') || value.contains("--") => return None,
+            CheckDigit::Luhn | CheckDigit::Isbn10 | CheckDigit::Isbn13 => value.replace('-', ""),
+            _ => value.to_string(),
+        };
+
+        let digits = |text: &str| text.chars().map(|c| c.to_digit(10)).collect::<Option<Vec<u32>>>();
+        let (payload, found) = match self {
+            CheckDigit::Iban => (format!("{}{}", &value.get(..2)?, value.get(4..)?), value.get(2..4)?),
+            CheckDigit::CorporateNumber => (value.get(1..)?.to_string(), value.get(..1)?),
+            _ => (value.get(..value.len().checked_sub(1)?)?.to_string(), value.get(value.len() - 1..)?),
+        };
+
+        let expected = match self {
+            CheckDigit::Luhn => {
+                let payload = digits(&payload).filter(|d| (1..=18).contains(&d.len()))?;
+                let sum: u32 = payload
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, d)| if i % 2 == 0 { (d * 2) / 10 + (d * 2) % 10 } else { *d })
+                    .sum();
+                ((10 - sum % 10) % 10).to_string()
+            }
+            CheckDigit::Isbn10 => {
+                let payload = digits(&payload).filter(|d| d.len() == 9)?;
+                let sum: u32 = payload.iter().zip(1..).map(|(d, weight)| d * weight).sum();
+                match sum % 11 {
+                    10 => "X".to_string(),
+                    check => check.to_string(),
+                }
+            }
+            CheckDigit::Isbn13 | CheckDigit::Ean => {
+                let payload = digits(&payload).filter(|d| match self {
+                    CheckDigit::Isbn13 => d.len() == 12 && (d[..3] == [9, 7, 8] || d[..3] == [9, 7, 9]),
+                    _ => d.len() == 7 || d.len() == 12,
+                })?;
+                let sum: u32 = payload
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+                    .sum();
+                ((10 - sum % 10) % 10).to_string()
+            }
+            CheckDigit::Iban => {
+                let is_country = payload.chars().take(2).all(|c| c.is_ascii_uppercase());
+                let is_account = payload.chars().skip(2).all(|c| c.is_ascii_digit() || c.is_ascii_uppercase());
+                if !is_country || !is_account || !(13..=32).contains(&payload.len()) || digits(found).is_none() {
+                    return None;
+                }
+                // The country code and check digits move to the end, and
+                // letters count as 10 to 35.
+                let rearranged = format!("{}{}00", &payload[2..], &payload[..2]);
+                let remainder = rearranged.chars().try_fold(0, |remainder, c| {
+                    Some(match c.to_digit(36)? {
+                        d if d < 10 => remainder * 10 + d,
+                        d => remainder * 100 + d,
+                    } % 97)
+                })?;
+                format!("{:02}", 98 - remainder)
+            }
+            CheckDigit::CorporateNumber => {
+                let payload = digits(&payload).filter(|d| d.len() == 12)?;
+                let sum: u32 = payload
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .map(|(i, d)| if i % 2 == 0 { *d } else { d * 2 })
+                    .sum();
+                (9 - sum % 9).to_string()
+            }
+        };
+
+        let is_check_digit = found.chars().all(|c| c.is_ascii_digit() || (*self == CheckDigit::Isbn10 && c == 'X'));
+        is_check_digit.then(|| (expected, found.to_string()))
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.check(value).is_some_and(|(expected, found)| expected == found)
+    }
+}
+
+impl std::fmt::Display for CheckDigit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CheckDigit::Luhn => write!(f, "luhn"),
+            CheckDigit::Isbn10 => write!(f, "isbn10"),
+            CheckDigit::Isbn13 => write!(f, "isbn13"),
+            CheckDigit::Ean => write!(f, "ean"),
+            CheckDigit::Iban => write!(f, "iban"),
+            CheckDigit::CorporateNumber => write!(f, "corporate_number"),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use super::error::DatasetError;
+use super::term::{Exact, Term};
+
+/// A field of the trailer line, by 1-based position or term name.
+#[derive(Debug, PartialEq)]
+pub enum Field {
+    Index(usize),
+    Name(String),
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Field::Index(index) => write!(f, "field {}", index),
+            Field::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum(String),
+}
+
+impl std::fmt::Display for Aggregate {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Aggregate::Count => write!(f, "count"),
+            Aggregate::Sum(column) => write!(f, "sum({})", column),
+        }
+    }
+}
+
+/// An assertion over the whole input, checked after the last line.
+#[derive(Debug, PartialEq)]
+pub enum Check {
+    Rows {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    Trailer {
+        field: Field,
+        aggregate: Aggregate,
+    },
+}
+
+#[derive(Debug)]
+pub struct TrailerField {
+    pub name: String,
+    pub value: String,
+    pub number: Option<Exact>,
+}
+
+/// Running totals of the data rows, which exclude header and trailer lines.
+#[derive(Debug, Default)]
+pub struct Tally {
+    pub rows: usize,
+    pub sums: HashMap<String, Exact>,
+    pub trailer: Option<Vec<TrailerField>>,
+}
+
+impl Tally {
+    pub fn add_row(self: &mut Tally, terms: &[Term], values: &[&str]) {
+        self.rows += 1;
+        for (term, value) in terms.iter().zip(values) {
+            if let Some(number) = term.exact(value) {
+                let sum = match self.sums.get(&term.name) {
+                    Some(sum) => sum.add(&number),
+                    None => number,
+                };
+                self.sums.insert(term.name.clone(), sum);
+            }
+        }
+    }
+
+    pub fn set_trailer(self: &mut Tally, terms: &[Term], values: &[&str]) {
+        let fields = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| TrailerField {
+                name: terms.get(i).map(|term| term.name.clone()).unwrap_or_default(),
+                value: value.to_string(),
+                number: terms
+                    .get(i)
+                    .and_then(|term| term.exact(value))
+                    .or_else(|| Exact::parse(value)),
+            })
+            .collect();
+        self.trailer = Some(fields);
+    }
+}
+
+impl Check {
+    pub fn validate(self: &Check, tally: &Tally) -> Result<(), DatasetError> {
+        match self {
+            Check::Rows { min, max } => {
+                let too_few = min.is_some_and(|min| tally.rows < min);
+                let too_many = max.is_some_and(|max| tally.rows > max);
+                if too_few || too_many {
+                    return Err(DatasetError::RowCountMismatch {
+                        expected: match (min, max) {
+                            (Some(min), Some(max)) if min == max => format!("{}", min),
+                            (Some(min), Some(max)) => format!("between {} and {}", min, max),
+                            (Some(min), None) => format!("at least {}", min),
+                            (None, Some(max)) => format!("at most {}", max),
+                            (None, None) => unreachable!(),
+                        },
+                        found: tally.rows,
+                    });
+                }
+                Ok(())
+            }
+            Check::Trailer { field, aggregate } => {
+                let Some(trailer) = &tally.trailer else {
+                    return Err(DatasetError::MissingTrailer);
+                };
+                let field_value = match field {
+                    Field::Index(index) => trailer.get(index - 1),
+                    Field::Name(name) => trailer.iter().find(|f| &f.name == name),
+                };
+
+                let total = match aggregate {
+                    Aggregate::Count => Exact::Integer(tally.rows as i128),
+                    Aggregate::Sum(column) => tally.sums.get(column).cloned().unwrap_or(Exact::Integer(0)),
+                };
+
+                match field_value.and_then(|f| f.number.as_ref()) {
+                    Some(number) if number.equals(&total) => Ok(()),
+                    _ => Err(DatasetError::TrailerMismatch {
+                        field: field.to_string(),
+                        aggregate: aggregate.to_string(),
+                        value: field_value.map(|f| f.value.clone()).unwrap_or_default(),
+                        total: total.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use super::error::SchemaError;
+use super::record::Role;
+use super::dataset::{Aggregate, Check, Field};
+use super::syntax::{split_args, split_call};
+use super::term::NumberFormat;
+
+#[derive(Debug, PartialEq)]
+pub enum Directive {
+    Record {
+        key: String,
+        role: Option<Role>,
+    },
+    When {
+        condition: String,
+    },
+    Assert {
+        expression: String,
+    },
+    Check(Check),
+    Locale(NumberFormat),
+}
+
+impl Directive {
+    pub fn from_text(text: &str) -> Result<Directive, SchemaError> {
+        let invalid = || SchemaError::InvalidDirective { text: text.to_string() };
+
+        let body = text.strip_prefix('@').ok_or_else(invalid)?;
+        let (name, args) = split_call(body).ok_or_else(invalid)?;
+
+        let expression = args.trim().to_string();
+        match name {
+            "when" if !expression.is_empty() => return Ok(Directive::When { condition: expression }),
+            "assert" if !expression.is_empty() => return Ok(Directive::Assert { expression }),
+            _ => {}
+        }
+
+        match (name, split_args(args).as_slice()) {
+            ("record", [key]) => Ok(Directive::Record {
+                key: key.to_string(),
+                role: None,
+            }),
+            ("record", [key, role]) => Ok(Directive::Record {
+                key: key.to_string(),
+                role: Some(Role::from_text(role).ok_or_else(invalid)?),
+            }),
+            ("locale", args) if !args.is_empty() => {
+                Ok(Directive::Locale(NumberFormat::from_args(args).ok_or_else(invalid)?))
+            }
+            ("rows", args) if !args.is_empty() => {
+                let (mut min, mut max) = (None, None);
+                for arg in args {
+                    match arg.split_once('=').map(|(k, v)| (k.trim(), v.trim().parse::<usize>())) {
+                        Some(("min", Ok(n))) => min = Some(n),
+                        Some(("max", Ok(n))) => max = Some(n),
+                        None => {
+                            let n = arg.parse::<usize>().map_err(|_| invalid())?;
+                            (min, max) = (Some(n), Some(n));
+                        }
+                        _ => return Err(invalid()),
+                    }
+                }
+                if let (Some(min), Some(max)) = (min, max) {
+                    if min > max {
+                        return Err(invalid());
+                    }
+                }
+                Ok(Directive::Check(Check::Rows { min, max }))
+            }
+            ("trailer", [arg]) => {
+                let (field, aggregate) = arg.split_once('=').ok_or_else(invalid)?;
+                let field = match field.trim().parse::<usize>() {
+                    Ok(0) => return Err(invalid()),
+                    Ok(index) => Field::Index(index),
+                    Err(_) => Field::Name(field.trim().to_string()),
+                };
+                let aggregate = match aggregate.trim() {
+                    "count" => Aggregate::Count,
+                    sum => {
+                        let column = sum
+                            .strip_prefix("sum(")
+                            .and_then(|sum| sum.strip_suffix(')'))
+                            .ok_or_else(invalid)?;
+                        Aggregate::Sum(column.trim().to_string())
+                    }
+                };
+                Ok(Directive::Check(Check::Trailer { field, aggregate }))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
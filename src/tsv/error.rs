@@ -1,11 +1,12 @@
 use thiserror::Error;
+use super::charclass::CharClass;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum SchemaError {
     #[error(r#"
     Error: Invalid Syntax
 
-    Expect: <id>:<type>
+    Expect: <id>:<type>, * or ...
     But, Found: {text}
     "#)]
     InvalidSyntax {
@@ -15,11 +16,110 @@ pub enum SchemaError {
     #[error(r#"
     Error: Invalid Type {type_}
 
-    Available types: integer, float, string, boolean, null
+    Available types: integer, float, decimal(<precision>, <scale>), duration, bytesize, string, boolean, null
+    Character classes: ascii, alnum, hiragana, katakana, kanji, halfwidth, fullwidth
+        or a Unicode script name such as latin, greek or cyrillic
+    Check digits: luhn, isbn10, isbn13, ean, jan, iban, corporate_number
+    Number formats: integer(<format>), float(<format>)
+        where <format> is en, de, ja, thousands='<char>', decimal='<char>' or fullwidth
     "#)]
     InvalidType {
         type_: String
-    }
+    },
+
+    #[error(r#"
+    Error: Invalid Directive
+
+    Expect: @record(<key>), @record(<key>, header|trailer), @when(<condition>) <id>:<type>,
+            @assert(<expression>), @rows(<n>), @rows(min=<n>, max=<n>),
+            @trailer(<field> = count), @trailer(<field> = sum(<id>)) or @locale(<format>)
+    But, Found: {text}
+    "#)]
+    InvalidDirective {
+        text: String
+    },
+
+    #[error(r#"
+    Error: Duplicate record {key}
+    "#)]
+    DuplicateRecord {
+        key: String
+    },
+
+    #[error(r#"
+    Error: Duplicate {role} record
+    "#)]
+    DuplicateRole {
+        role: String
+    },
+
+    #[error(r#"
+    Error: Term {name} is declared outside of any @record
+    "#)]
+    TermOutsideRecord {
+        name: String
+    },
+
+    #[error(r#"
+    Error: Directive {text} is declared outside of any @record
+    "#)]
+    DirectiveOutsideRecord {
+        text: String
+    },
+
+    #[error(r#"
+    Error: Term {name} is declared after the rest of line term ...
+    "#)]
+    TermAfterRest {
+        name: String
+    },
+
+    #[error(r#"
+    Error: Invalid Expression {text}
+
+    Reason: {reason}
+    "#)]
+    InvalidExpression {
+        text: String,
+        reason: String,
+    },
+
+    #[error(r#"
+    Error: Expression type mismatch
+
+    Expected operand of {expression} is {expected}, But Found: {found}
+    "#)]
+    ExpressionTypeMismatch {
+        expression: String,
+        expected: String,
+        found: String,
+    },
+
+    #[error(r#"
+    Error: @trailer needs a trailer record, declared as @record(<key>, trailer)
+    "#)]
+    MissingTrailerRecord,
+
+    #[error(r#"
+    Error: Unknown term {name}
+    "#)]
+    UnknownTerm {
+        name: String
+    },
+
+    #[error(r#"
+    Error: Unknown function {name}
+
+    Available functions: len(text), abs(number)
+    "#)]
+    UnknownFunction {
+        name: String
+    },
+
+    #[error(r#"
+    Error: @when must be followed by a term
+    "#)]
+    MissingConditionalTerm,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -28,32 +128,156 @@ pub struct SchemaErrors(pub Vec<SchemaError>);
 impl std::fmt::Display for SchemaErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         for error in &self.0 {
-            write!(f, "{}\n", error)?;
+            writeln!(f, "{}", error)?;
         }
         Ok(())
     }
 }
 
+/// Where a field is in its line: the term it is checked against, its 1-based
+/// column and its byte offset in the line.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Column {
+    pub name: String,
+    pub index: usize,
+    pub offset: usize,
+}
+
+impl std::fmt::Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} (column {}, byte {})", self.name, self.index, self.offset)
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ValidateLineError {
     #[error(r#"
-    Error: Data type mismatch
+    Error: Data type mismatch at {column}
 
     Expected type is {type_}, But Found: {value}
     "#)]
     DataTypeMismatch {
+        column: Column,
         type_: String,
         value: String
     },
 
     #[error(r#"
-        Error: Field number mismatch
+    Error: Invalid character at {column}
+
+    Expected type is {type_}, But Found: {value}
+    Character {position} '{character}' ({code_point}) is not allowed
+    "#)]
+    InvalidCharacter {
+        column: Column,
+        type_: String,
+        value: String,
+        character: char,
+        code_point: String,
+        position: usize,
+    },
+
+    #[error(r#"
+    Error: Check digit mismatch at {column}
+
+    Expected type is {type_}, But Found: {value}
+    Check digit should be {expected}, But Found: {found}
+    "#)]
+    CheckDigitMismatch {
+        column: Column,
+        type_: String,
+        value: String,
+        expected: String,
+        found: String,
+    },
+
+    #[error(r#"
+    Error: Line too long
+
+    Expected at most {limit} bytes, But Found: {length}
+    "#)]
+    LineTooLong {
+        limit: usize,
+        length: usize,
+    },
+
+    #[error(r#"
+    Error: Too many fields
+
+    Expected at most {limit} fields, But Found: {found}
+    "#)]
+    TooManyFields {
+        limit: usize,
+        found: usize,
+    },
+
+    #[error(r#"
+        Error: Field number mismatch at column {index}, byte {offset}
         Expected: {expected}, But Found: {found}
     "#)]
     FieldNumberMismatch {
-        expected: usize,
+        expected: String,
         found: usize,
-    }
+        index: usize,
+        offset: usize,
+    },
+
+    #[error(r#"
+    Error: Unknown record at {column}
+
+    Expected record is {expected}, But Found: {found}
+    "#)]
+    UnknownRecord {
+        column: Column,
+        expected: String,
+        found: String,
+    },
+
+    #[error(r#"
+    Error: Missing {role} record
+
+    Expected record {key} at the {position} line, But Found: {found}
+    "#)]
+    MissingRecord {
+        role: String,
+        key: String,
+        position: String,
+        found: String,
+    },
+
+    #[error(r#"
+    Error: Misplaced {role} record
+
+    Record {key} must appear only at the {position} line
+    "#)]
+    MisplacedRecord {
+        role: String,
+        key: String,
+        position: String,
+    },
+
+    #[error(r#"
+    Error: Conditional constraint failed at {column}
+
+    When {condition}, Expected {} is {type_}, But Found: {value}
+    "#, .column.name)]
+    ConditionalMismatch {
+        column: Column,
+        condition: String,
+        type_: String,
+        value: String,
+    },
+
+    #[error(r#"
+    Error: Assertion failed at {}
+
+    Expected {expression}, But Found: {values}
+    "#, .columns.iter().map(Column::to_string).collect::<Vec<String>>().join(", "))]
+    AssertionFailed {
+        columns: Vec<Column>,
+        expression: String,
+        values: String,
+    },
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -61,35 +285,158 @@ pub struct ValidateLineErrors(pub Vec<ValidateLineError>);
 
 impl std::fmt::Display for ValidateLineErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "----------------\n")?;
+        writeln!(f, "----------------")?;
         for error in &self.0 {
-            write!(f, "{}\n", error)?;
-            write!(f, "----------------\n")?;
+            writeln!(f, "{}", error)?;
+            writeln!(f, "----------------")?;
         }
         Ok(())
     }
 }
 
-#[derive(Error, Debug, PartialEq)]
-#[error(r#"
-Error: Validation failed at line {line_number}
-Raw text: {line_text}
+impl ValidateLineError {
+    /// The byte range of the line the error points at, if it is about a
+    /// single field.
+    pub fn span(&self) -> Option<(usize, usize)> {
+        match self {
+            ValidateLineError::DataTypeMismatch { column, value, .. }
+            | ValidateLineError::CheckDigitMismatch { column, value, .. }
+            | ValidateLineError::ConditionalMismatch { column, value, .. }
+            | ValidateLineError::UnknownRecord { column, found: value, .. } => {
+                Some((column.offset, column.offset + value.len()))
+            }
+            ValidateLineError::InvalidCharacter { column, value, character, position, .. } => {
+                let start = column.offset + value.char_indices().nth(position - 1)?.0;
+                Some((start, start + character.len_utf8()))
+            }
+            _ => None,
+        }
+    }
+}
 
-{errors}
-"#)]
+#[derive(Error, Debug, PartialEq)]
 pub struct ValidationError {
     pub line_text: String,
     pub line_number: usize,
     pub errors: ValidateLineErrors,
 }
 
+impl ValidationError {
+    /// A line of carets under the fields the errors point at, aligned with
+    /// the line text. Tabs are kept and full-width characters take two
+    /// columns, so that the carets line up in a terminal.
+    fn carets(&self) -> Option<String> {
+        let spans = self.errors.0.iter().filter_map(|error| error.span()).collect::<Vec<_>>();
+        let end = spans.iter().map(|(_, end)| *end).max()?;
+
+        let mut carets = String::new();
+        for (i, c) in self.line_text.char_indices().take_while(|(i, _)| *i < end) {
+            let width = if CharClass::Fullwidth.contains(c) { 2 } else { 1 };
+            let marker = match spans.iter().any(|(start, end)| (*start..*end).contains(&i)) {
+                true => '^',
+                false if c == '\t' => '\t',
+                false => ' ',
+            };
+            carets.extend(std::iter::repeat_n(marker, if marker == '\t' { 1 } else { width }));
+        }
+        Some(carets)
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "Error: Validation failed at line {}", self.line_number)?;
+        writeln!(f, "Raw text: {}", self.line_text)?;
+        if let Some(carets) = self.carets() {
+            writeln!(f, "          {}", carets)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}", self.errors)
+    }
+}
+
 #[derive(Error, Debug, PartialEq)]
-pub struct ValidationErrors(pub Vec<ValidationError>);
+pub enum DatasetError {
+    #[error(r#"
+    Error: Row count mismatch
 
-impl std::fmt::Display for ValidationErrors {
+    Expected: {expected}, But Found: {found}
+    "#)]
+    RowCountMismatch {
+        expected: String,
+        found: usize,
+    },
+
+    #[error(r#"
+    Error: Trailer mismatch
+
+    Expected {field} of the trailer is {aggregate} = {total}, But Found: {value}
+    "#)]
+    TrailerMismatch {
+        field: String,
+        aggregate: String,
+        value: String,
+        total: String,
+    },
+
+    #[error(r#"
+    Error: Trailer not found
+    "#)]
+    MissingTrailer,
+
+    #[error(r#"
+    Error: Missing {role} record
+
+    Expected record {key} at the {position} line, But the input is empty
+    "#)]
+    MissingRecord {
+        role: String,
+        key: String,
+        position: String,
+    },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub struct DatasetErrors(pub Vec<DatasetError>);
+
+impl std::fmt::Display for DatasetErrors {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "----------------")?;
         for error in &self.0 {
-            write!(f, "{}\n", error)?;
+            writeln!(f, "{}", error)?;
+            writeln!(f, "----------------")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Error: {count} more lines failed validation, beyond the limit of {limit} errors")]
+pub struct OmittedErrors {
+    pub count: usize,
+    pub limit: usize,
+}
+
+/// Errors of a whole input: per-line errors, followed by errors of the
+/// dataset assertions checked after the last line.
+#[derive(Error, Debug, PartialEq)]
+pub struct ValidationErrors {
+    pub lines: Vec<ValidationError>,
+    pub omitted: Option<OmittedErrors>,
+    pub dataset: DatasetErrors,
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for error in &self.lines {
+            writeln!(f, "{}", error)?;
+        }
+        if let Some(omitted) = &self.omitted {
+            writeln!(f, "{}", omitted)?;
+        }
+        if !self.dataset.0.is_empty() {
+            writeln!(f, "\nError: Dataset validation failed\n\n{}", self.dataset)?;
         }
         Ok(())
     }
@@ -0,0 +1,504 @@
+use std::collections::HashMap;
+use super::error::SchemaError;
+use super::term::{Term, Type};
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Number(String),
+    Operator(String),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Kind {
+    Number,
+    Text,
+    Boolean,
+}
+
+impl Kind {
+    /// The kind of a column, derived from its declared types. `null` is
+    /// ignored since null values are never compared.
+    pub fn of(types: &[Type]) -> Kind {
+        let mut kinds = types.iter().filter_map(|type_| match type_ {
+            Type::Integer(_) | Type::Float(_) | Type::Decimal(_) | Type::Unit(_) => Some(Kind::Number),
+            Type::Boolean => Some(Kind::Boolean),
+            Type::Null => None,
+            _ => Some(Kind::Text),
+        });
+        match kinds.next() {
+            Some(kind) if kinds.all(|other| other == kind) => kind,
+            _ => Kind::Text,
+        }
+    }
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Kind::Number => write!(f, "number"),
+            Kind::Text => write!(f, "text"),
+            Kind::Boolean => write!(f, "boolean"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    And,
+    Or,
+}
+
+impl std::fmt::Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Operator::Eq => write!(f, "="),
+            Operator::Ne => write!(f, "!="),
+            Operator::Lt => write!(f, "<"),
+            Operator::Le => write!(f, "<="),
+            Operator::Gt => write!(f, ">"),
+            Operator::Ge => write!(f, ">="),
+            Operator::Add => write!(f, "+"),
+            Operator::Sub => write!(f, "-"),
+            Operator::Mul => write!(f, "*"),
+            Operator::Div => write!(f, "/"),
+            Operator::And => write!(f, "and"),
+            Operator::Or => write!(f, "or"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Expr {
+    Column(String),
+    Literal(Value),
+    Not(Box<Expr>),
+    Call {
+        function: String,
+        args: Vec<Expr>,
+    },
+    Binary {
+        operator: Operator,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Boolean(bool),
+    Null,
+}
+
+impl Value {
+    /// Convert a raw field to a value of the kind of its term. Fields which
+    /// do not fit the kind, such as `_`, become null.
+    pub fn from_field(field: &str, term: &Term) -> Value {
+        match Kind::of(&term.types) {
+            Kind::Number => term.number(field).map(Value::Number).unwrap_or(Value::Null),
+            Kind::Text if field == "_" => Value::Null,
+            Kind::Text => Value::Text(field.to_string()),
+            Kind::Boolean => match field.to_lowercase().as_str() {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "'{}'", text),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Null => write!(f, "null"),
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expr::Column(name) => write!(f, "{}", name),
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Not(expr) => write!(f, "not {}", expr),
+            Expr::Call { function, args } => {
+                let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+                write!(f, "{}({})", function, args.join(", "))
+            }
+            Expr::Binary { operator, lhs, rhs } => write!(f, "({} {} {})", lhs, operator, rhs),
+        }
+    }
+}
+
+impl Expr {
+    pub fn from_text(text: &str) -> Result<Expr, SchemaError> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser { text, tokens, position: 0 };
+        let expr = parser.or()?;
+        if parser.position != parser.tokens.len() {
+            return Err(parser.invalid("unexpected trailing input"));
+        }
+        Ok(expr)
+    }
+
+    /// Column names referenced by the expression, in order of appearance.
+    pub fn columns(&self) -> Vec<&str> {
+        let mut columns = vec![];
+        self.collect_columns(&mut columns);
+        columns
+    }
+
+    fn collect_columns<'a>(&'a self, columns: &mut Vec<&'a str>) {
+        match self {
+            Expr::Column(name) => {
+                if !columns.contains(&name.as_str()) {
+                    columns.push(name);
+                }
+            }
+            Expr::Literal(_) => {}
+            Expr::Not(expr) => expr.collect_columns(columns),
+            Expr::Call { args, .. } => args.iter().for_each(|arg| arg.collect_columns(columns)),
+            Expr::Binary { lhs, rhs, .. } => {
+                lhs.collect_columns(columns);
+                rhs.collect_columns(columns);
+            }
+        }
+    }
+
+    /// Check that every referenced column exists and operands have the
+    /// kinds their operators expect.
+    pub fn kind(&self, columns: &HashMap<&str, Kind>) -> Result<Kind, SchemaError> {
+        match self {
+            Expr::Column(name) => columns
+                .get(name.as_str())
+                .copied()
+                .ok_or_else(|| SchemaError::UnknownTerm { name: name.clone() }),
+            Expr::Literal(Value::Number(_)) => Ok(Kind::Number),
+            Expr::Literal(Value::Boolean(_)) => Ok(Kind::Boolean),
+            Expr::Literal(_) => Ok(Kind::Text),
+            Expr::Not(expr) => {
+                self.expect(expr, Kind::Boolean, columns)?;
+                Ok(Kind::Boolean)
+            }
+            Expr::Call { function, args } => match (function.as_str(), args.as_slice()) {
+                ("len", [arg]) => {
+                    self.expect(arg, Kind::Text, columns)?;
+                    Ok(Kind::Number)
+                }
+                ("abs", [arg]) => {
+                    self.expect(arg, Kind::Number, columns)?;
+                    Ok(Kind::Number)
+                }
+                _ => Err(SchemaError::UnknownFunction { name: self.to_string() }),
+            },
+            Expr::Binary { operator, lhs, rhs } => match operator {
+                Operator::Eq | Operator::Ne | Operator::Lt | Operator::Le | Operator::Gt | Operator::Ge => {
+                    let kind = lhs.kind(columns)?;
+                    self.expect(rhs, kind, columns)?;
+                    Ok(Kind::Boolean)
+                }
+                Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => {
+                    self.expect(lhs, Kind::Number, columns)?;
+                    self.expect(rhs, Kind::Number, columns)?;
+                    Ok(Kind::Number)
+                }
+                Operator::And | Operator::Or => {
+                    self.expect(lhs, Kind::Boolean, columns)?;
+                    self.expect(rhs, Kind::Boolean, columns)?;
+                    Ok(Kind::Boolean)
+                }
+            },
+        }
+    }
+
+    fn expect(&self, operand: &Expr, expected: Kind, columns: &HashMap<&str, Kind>) -> Result<(), SchemaError> {
+        let found = operand.kind(columns)?;
+        if found != expected {
+            return Err(SchemaError::ExpressionTypeMismatch {
+                expression: self.to_string(),
+                expected: expected.to_string(),
+                found: found.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Evaluate the expression. Null operands make the result null, so a
+    /// constraint over a null column is neither satisfied nor violated.
+    pub fn evaluate(&self, values: &HashMap<&str, Value>) -> Value {
+        match self {
+            Expr::Column(name) => values[name.as_str()].clone(),
+            Expr::Literal(value) => value.clone(),
+            Expr::Not(expr) => match expr.evaluate(values) {
+                Value::Boolean(boolean) => Value::Boolean(!boolean),
+                _ => Value::Null,
+            },
+            Expr::Call { function, args } => match (function.as_str(), args[0].evaluate(values)) {
+                ("len", Value::Text(text)) => Value::Number(text.chars().count() as f64),
+                ("abs", Value::Number(number)) => Value::Number(number.abs()),
+                _ => Value::Null,
+            },
+            Expr::Binary { operator, lhs, rhs } => {
+                let lhs = lhs.evaluate(values);
+                let rhs = rhs.evaluate(values);
+                match (operator, lhs, rhs) {
+                    (_, Value::Null, _) | (_, _, Value::Null) => Value::Null,
+                    (Operator::Eq, Value::Number(lhs), Value::Number(rhs)) => Value::Boolean(approx_eq(lhs, rhs)),
+                    (Operator::Ne, Value::Number(lhs), Value::Number(rhs)) => Value::Boolean(!approx_eq(lhs, rhs)),
+                    (Operator::Eq, lhs, rhs) => Value::Boolean(lhs == rhs),
+                    (Operator::Ne, lhs, rhs) => Value::Boolean(lhs != rhs),
+                    (Operator::Lt, lhs, rhs) => Value::Boolean(compare(&lhs, &rhs).is_lt()),
+                    (Operator::Le, lhs, rhs) => Value::Boolean(compare(&lhs, &rhs).is_le()),
+                    (Operator::Gt, lhs, rhs) => Value::Boolean(compare(&lhs, &rhs).is_gt()),
+                    (Operator::Ge, lhs, rhs) => Value::Boolean(compare(&lhs, &rhs).is_ge()),
+                    (Operator::Add, Value::Number(lhs), Value::Number(rhs)) => Value::Number(lhs + rhs),
+                    (Operator::Sub, Value::Number(lhs), Value::Number(rhs)) => Value::Number(lhs - rhs),
+                    (Operator::Mul, Value::Number(lhs), Value::Number(rhs)) => Value::Number(lhs * rhs),
+                    (Operator::Div, Value::Number(_), Value::Number(0.0)) => Value::Null,
+                    (Operator::Div, Value::Number(lhs), Value::Number(rhs)) => Value::Number(lhs / rhs),
+                    (Operator::And, Value::Boolean(lhs), Value::Boolean(rhs)) => Value::Boolean(lhs && rhs),
+                    (Operator::Or, Value::Boolean(lhs), Value::Boolean(rhs)) => Value::Boolean(lhs || rhs),
+                    _ => unreachable!("expression is type-checked at schema time"),
+                }
+            }
+        }
+    }
+}
+
+/// Compare numbers with a relative tolerance, so that `total = price * qty`
+/// holds for decimal amounts such as `0.1 * 3`.
+fn approx_eq(lhs: f64, rhs: f64) -> bool {
+    (lhs - rhs).abs() <= f64::EPSILON * 16.0 * lhs.abs().max(rhs.abs()).max(1.0)
+}
+
+fn compare(lhs: &Value, rhs: &Value) -> std::cmp::Ordering {
+    match (lhs, rhs) {
+        (Value::Number(lhs), Value::Number(rhs)) => lhs.total_cmp(rhs),
+        (Value::Text(lhs), Value::Text(rhs)) => lhs.cmp(rhs),
+        (Value::Boolean(lhs), Value::Boolean(rhs)) => lhs.cmp(rhs),
+        _ => unreachable!("expression is type-checked at schema time"),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, SchemaError> {
+    let invalid = |reason: &str| SchemaError::InvalidExpression {
+        text: text.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => tokens.push(Token::LeftParen),
+            ')' => tokens.push(Token::RightParen),
+            ',' => tokens.push(Token::Comma),
+            '=' | '+' | '-' | '*' | '/' => tokens.push(Token::Operator(c.to_string())),
+            '!' | '<' | '>' => {
+                let operator = match chars.next_if_eq(&'=') {
+                    Some(_) => format!("{}=", c),
+                    None if c == '!' => return Err(invalid("unexpected character !")),
+                    None => c.to_string(),
+                };
+                tokens.push(Token::Operator(operator));
+            }
+            '\'' | '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c => break,
+                        Some(char) => string.push(char),
+                        None => return Err(invalid("unterminated string")),
+                    }
+                }
+                tokens.push(Token::String(string));
+            }
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some(d) = chars.next_if(|d| d.is_ascii_digit() || *d == '.') {
+                    number.push(d);
+                }
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut identifier = c.to_string();
+                while let Some(d) = chars.next_if(|d| d.is_alphanumeric() || *d == '_') {
+                    identifier.push(d);
+                }
+                tokens.push(Token::Identifier(identifier));
+            }
+            c => return Err(invalid(&format!("unexpected character {}", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn invalid(&self, reason: &str) -> SchemaError {
+        SchemaError::InvalidExpression {
+            text: self.text.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn consume(&mut self, token: Token) -> bool {
+        if self.peek() == Some(&token) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn consume_operator(&mut self, operators: &[(&str, Operator)]) -> Option<Operator> {
+        if let Some(Token::Operator(op)) = self.peek() {
+            let operator = operators.iter().find(|(text, _)| text == op).map(|(_, operator)| *operator);
+            if operator.is_some() {
+                self.position += 1;
+            }
+            return operator;
+        }
+        None
+    }
+
+    fn binary(lhs: Expr, operator: Operator, rhs: Expr) -> Expr {
+        Expr::Binary { operator, lhs: Box::new(lhs), rhs: Box::new(rhs) }
+    }
+
+    fn or(&mut self) -> Result<Expr, SchemaError> {
+        let mut lhs = self.and()?;
+        while self.consume(Token::Identifier("or".to_string())) {
+            lhs = Self::binary(lhs, Operator::Or, self.and()?);
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, SchemaError> {
+        let mut lhs = self.not()?;
+        while self.consume(Token::Identifier("and".to_string())) {
+            lhs = Self::binary(lhs, Operator::And, self.not()?);
+        }
+        Ok(lhs)
+    }
+
+    fn not(&mut self) -> Result<Expr, SchemaError> {
+        if self.consume(Token::Identifier("not".to_string())) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, SchemaError> {
+        let lhs = self.additive()?;
+        let operators = [
+            ("=", Operator::Eq),
+            ("!=", Operator::Ne),
+            ("<", Operator::Lt),
+            ("<=", Operator::Le),
+            (">", Operator::Gt),
+            (">=", Operator::Ge),
+        ];
+        match self.consume_operator(&operators) {
+            Some(operator) => Ok(Self::binary(lhs, operator, self.additive()?)),
+            None => Ok(lhs),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, SchemaError> {
+        let mut lhs = self.multiplicative()?;
+        while let Some(operator) = self.consume_operator(&[("+", Operator::Add), ("-", Operator::Sub)]) {
+            lhs = Self::binary(lhs, operator, self.multiplicative()?);
+        }
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, SchemaError> {
+        let mut lhs = self.unary()?;
+        while let Some(operator) = self.consume_operator(&[("*", Operator::Mul), ("/", Operator::Div)]) {
+            lhs = Self::binary(lhs, operator, self.unary()?);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, SchemaError> {
+        if self.consume(Token::Operator("-".to_string())) {
+            let operand = self.unary()?;
+            return Ok(Self::binary(Expr::Literal(Value::Number(0.0)), Operator::Sub, operand));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, SchemaError> {
+        match self.next() {
+            Some(Token::Identifier(name)) if name == "true" => Ok(Expr::Literal(Value::Boolean(true))),
+            Some(Token::Identifier(name)) if name == "false" => Ok(Expr::Literal(Value::Boolean(false))),
+            Some(Token::Identifier(name)) if self.consume(Token::LeftParen) => {
+                let mut args = vec![];
+                if !self.consume(Token::RightParen) {
+                    loop {
+                        args.push(self.or()?);
+                        if self.consume(Token::RightParen) {
+                            break;
+                        }
+                        if !self.consume(Token::Comma) {
+                            return Err(self.invalid("expected , or )"));
+                        }
+                    }
+                }
+                Ok(Expr::Call { function: name, args })
+            }
+            Some(Token::Identifier(name)) => Ok(Expr::Column(name)),
+            Some(Token::String(text)) => Ok(Expr::Literal(Value::Text(text))),
+            Some(Token::Number(number)) => number
+                .parse::<f64>()
+                .map(|number| Expr::Literal(Value::Number(number)))
+                .map_err(|_| self.invalid(&format!("invalid number {}", number))),
+            Some(Token::LeftParen) => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RightParen) => Ok(expr),
+                    _ => Err(self.invalid("expected )")),
+                }
+            }
+            Some(token) => Err(self.invalid(&format!("unexpected token {:?}", token))),
+            None => Err(self.invalid("unexpected end of expression")),
+        }
+    }
+}
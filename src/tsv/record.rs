@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use super::term::Term;
+use super::expr::{Expr, Kind, Value};
+use super::error::{Column, SchemaError, ValidateLineError, ValidateLineErrors};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Role {
+    Header,
+    Trailer,
+}
+
+impl Role {
+    pub fn from_text(text: &str) -> Option<Role> {
+        match text {
+            "header" => Some(Role::Header),
+            "trailer" => Some(Role::Trailer),
+            _ => None,
+        }
+    }
+
+    pub fn position(&self) -> &'static str {
+        match self {
+            Role::Header => "first",
+            Role::Trailer => "last",
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Role::Header => write!(f, "header"),
+            Role::Trailer => write!(f, "trailer"),
+        }
+    }
+}
+
+/// A term checked only on lines where its condition holds, declared as
+/// `@when(<condition>) <id>:<type>`.
+#[derive(Debug, PartialEq)]
+pub struct Conditional {
+    pub source: String,
+    pub condition: Expr,
+    pub term: Term,
+}
+
+/// A row invariant over the terms of a record, declared as
+/// `@assert(<expression>)`.
+#[derive(Debug, PartialEq)]
+pub struct Assertion {
+    pub source: String,
+    pub expr: Expr,
+}
+
+/// A line layout. A schema without `@record` directives has a single record
+/// without a key, which applies to every line. A record ending with the `...`
+/// term accepts any number of fields after its terms.
+#[derive(Debug, PartialEq)]
+pub struct Record {
+    pub key: Option<String>,
+    pub role: Option<Role>,
+    pub terms: Vec<Term>,
+    pub conditionals: Vec<Conditional>,
+    pub assertions: Vec<Assertion>,
+    pub rest: bool,
+}
+
+impl Record {
+    pub fn new(key: Option<String>, role: Option<Role>) -> Record {
+        Record {
+            key,
+            role,
+            terms: vec![],
+            conditionals: vec![],
+            assertions: vec![],
+            rest: false,
+        }
+    }
+
+    pub fn add_term(self: &mut Record, term: Term) -> Result<(), SchemaError> {
+        if self.rest {
+            return Err(SchemaError::TermAfterRest { name: term.name });
+        }
+        self.terms.push(term);
+        Ok(())
+    }
+
+    pub fn set_rest(self: &mut Record) -> Result<(), SchemaError> {
+        if self.rest {
+            return Err(SchemaError::TermAfterRest { name: "...".to_string() });
+        }
+        self.rest = true;
+        Ok(())
+    }
+
+    fn accepts_field_number(self: &Record, found: usize) -> bool {
+        match self.rest {
+            true => found >= self.terms.len(),
+            false => found == self.terms.len(),
+        }
+    }
+
+    pub fn add_conditional(self: &mut Record, conditional: Conditional) {
+        self.conditionals.push(conditional);
+    }
+
+    pub fn add_assertion(self: &mut Record, assertion: Assertion) {
+        self.assertions.push(assertion);
+    }
+
+    fn kinds(self: &Record) -> HashMap<&str, Kind> {
+        self.terms
+            .iter()
+            .map(|term| (term.name.as_str(), Kind::of(&term.types)))
+            .collect()
+    }
+
+    /// Type-check the conditions and assertions once every term of the
+    /// record is declared.
+    pub fn check(self: &Record) -> Vec<SchemaError> {
+        let kinds = self.kinds();
+        let mut errors = vec![];
+
+        let expressions = self.conditionals
+            .iter()
+            .map(|conditional| &conditional.condition)
+            .chain(self.assertions.iter().map(|assertion| &assertion.expr));
+        for expr in expressions {
+            match expr.kind(&kinds) {
+                Ok(Kind::Boolean) => {}
+                Ok(kind) => errors.push(SchemaError::ExpressionTypeMismatch {
+                    expression: expr.to_string(),
+                    expected: Kind::Boolean.to_string(),
+                    found: kind.to_string(),
+                }),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        for conditional in &self.conditionals {
+            if !kinds.contains_key(conditional.term.name.as_str()) {
+                errors.push(SchemaError::UnknownTerm { name: conditional.term.name.clone() });
+            }
+        }
+
+        errors
+    }
+
+    /// Rewrite the fields whose type asks for normalization, keeping the
+    /// whitespace between fields as it is.
+    pub fn normalize(self: &Record, line: &str) -> Option<String> {
+        let fields = split_fields(line);
+        if !self.accepts_field_number(fields.len()) {
+            return None;
+        }
+
+        let mut normalized = String::new();
+        let mut end = 0;
+        for (term, (offset, field)) in self.terms.iter().zip(fields) {
+            if let Some(value) = term.normalize(field) {
+                normalized.push_str(&line[end..offset]);
+                normalized.push_str(&value);
+                end = offset + field.len();
+            }
+        }
+
+        if end == 0 {
+            return None;
+        }
+        normalized.push_str(&line[end..]);
+        Some(normalized)
+    }
+
+    fn column(self: &Record, index: usize, fields: &[(usize, &str)]) -> Column {
+        Column {
+            name: self.terms[index].name.clone(),
+            index: index + 1,
+            offset: fields[index].0,
+        }
+    }
+
+    /// Validate the fields of a line, given with their byte offsets.
+    pub fn validate(self: &Record, fields: &[(usize, &str)]) -> Result<(), ValidateLineErrors> {
+        if !self.accepts_field_number(fields.len()) {
+            // The mismatch is at the first field too many, or just after the
+            // last field when there are too few
+            let (index, offset) = match fields.get(self.terms.len()) {
+                Some((offset, _)) if !self.rest => (self.terms.len() + 1, *offset),
+                _ => (
+                    fields.len() + 1,
+                    fields.last().map(|(offset, value)| offset + value.len()).unwrap_or(0),
+                ),
+            };
+            return Err(ValidateLineErrors(
+                vec![
+                    ValidateLineError::FieldNumberMismatch {
+                        expected: match self.rest {
+                            true => format!("at least {}", self.terms.len()),
+                            false => self.terms.len().to_string(),
+                        },
+                        found: fields.len(),
+                        index,
+                        offset,
+                    }
+                ]
+            ));
+        }
+
+        let mut errors = vec![];
+
+        for (i, (term, (_, value))) in self.terms.iter().zip(fields).enumerate() {
+            if !term.accepts(value) {
+                errors.push(term.mismatch(value, self.column(i, fields)));
+            }
+        }
+
+        if !self.conditionals.is_empty() || !self.assertions.is_empty() {
+            errors.extend(self.validate_expressions(fields));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidateLineErrors(errors))
+        }
+    }
+
+    fn validate_expressions(self: &Record, fields: &[(usize, &str)]) -> Vec<ValidateLineError> {
+        let indices = self.terms
+            .iter()
+            .enumerate()
+            .map(|(i, term)| (term.name.as_str(), i))
+            .collect::<HashMap<&str, usize>>();
+        let values = self.terms
+            .iter()
+            .zip(fields)
+            .map(|(term, (_, field))| (term.name.as_str(), Value::from_field(field, term)))
+            .collect::<HashMap<&str, Value>>();
+
+        let mut errors = vec![];
+
+        for conditional in &self.conditionals {
+            if conditional.condition.evaluate(&values) != Value::Boolean(true) {
+                continue;
+            }
+            // Whatever the error, the field fails the conditional type
+            let index = indices[conditional.term.name.as_str()];
+            let value = fields[index].1;
+            if !conditional.term.accepts(value) {
+                errors.push(ValidateLineError::ConditionalMismatch {
+                    column: self.column(index, fields),
+                    condition: conditional.source.clone(),
+                    type_: conditional.term.type_text(),
+                    value: value.to_string(),
+                });
+            }
+        }
+
+        for assertion in &self.assertions {
+            if assertion.expr.evaluate(&values) != Value::Boolean(false) {
+                continue;
+            }
+            errors.push(ValidateLineError::AssertionFailed {
+                columns: assertion.expr
+                    .columns()
+                    .iter()
+                    .map(|name| self.column(indices[name], fields))
+                    .collect(),
+                expression: assertion.source.clone(),
+                values: assertion.expr
+                    .columns()
+                    .iter()
+                    .map(|name| format!("{}={}", name, fields[indices[name]].1))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            });
+        }
+
+        errors
+    }
+}
+
+/// Split a line on whitespace like `str::split_whitespace`, with the byte
+/// offset of each field.
+pub fn split_fields(line: &str) -> Vec<(usize, &str)> {
+    line.split_whitespace()
+        .map(|field| (field.as_ptr() as usize - line.as_ptr() as usize, field))
+        .collect()
+}
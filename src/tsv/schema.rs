@@ -1,6 +1,17 @@
-use std::io::BufRead;
-use super::term::Term;
+use crate::hygiene::Lines;
+use crate::limits::Limits;
+use super::term::{NumberFormat, Term};
+use super::record::{split_fields, Assertion, Conditional, Record, Role};
+use super::expr::Expr;
+use super::directive::Directive;
+use super::syntax::split_tokens;
+use super::dataset::{Aggregate, Check, Field, Tally};
 use super::error::{
+    Column,
+    DatasetError,
+    DatasetErrors,
+    OmittedErrors,
+    SchemaError,
     SchemaErrors,
     ValidationError,
     ValidationErrors,
@@ -10,30 +21,126 @@ use super::error::{
 
 #[derive(Debug, PartialEq)]
 pub struct Schema {
-    terms: Vec<Term>,
+    records: Vec<Record>,
+    checks: Vec<Check>,
 }
 
 impl Schema {
     fn new() -> Schema {
-        Schema { terms: vec![] }
+        Schema {
+            records: vec![Record::new(None, None)],
+            checks: vec![],
+        }
+    }
+
+    fn add_term(self: &mut Schema, term: Term) -> Result<(), SchemaError> {
+        self.records.last_mut().unwrap().add_term(term)
+    }
+
+    fn set_rest(self: &mut Schema) -> Result<(), SchemaError> {
+        self.records.last_mut().unwrap().set_rest()
+    }
+
+    fn add_conditional(self: &mut Schema, conditional: Conditional) {
+        self.records.last_mut().unwrap().add_conditional(conditional);
+    }
+
+    fn add_assertion(self: &mut Schema, assertion: Assertion) {
+        self.records.last_mut().unwrap().add_assertion(assertion);
     }
 
-    fn add_term(self: &mut Schema, term: Term) {
-        self.terms.push(term);
+    fn add_record(self: &mut Schema, record: Record) -> Result<(), SchemaError> {
+        if let Some(key) = &record.key {
+            if self.records.iter().any(|r| r.key.as_ref() == Some(key)) {
+                return Err(SchemaError::DuplicateRecord { key: key.clone() });
+            }
+        }
+        if let Some(role) = record.role {
+            if self.records.iter().any(|r| r.role == Some(role)) {
+                return Err(SchemaError::DuplicateRole { role: role.to_string() });
+            }
+        }
+        // The implicit record before the first `@record` must be empty. It
+        // is kept on error so that the terms which follow still have a record
+        if let Some(unkeyed) = self.records.first().filter(|record| record.key.is_none()) {
+            if let Some(term) = unkeyed.terms.first() {
+                return Err(SchemaError::TermOutsideRecord { name: term.name.clone() });
+            }
+            if unkeyed.rest {
+                return Err(SchemaError::TermOutsideRecord { name: "...".to_string() });
+            }
+            if let Some(conditional) = unkeyed.conditionals.first() {
+                return Err(SchemaError::DirectiveOutsideRecord { text: format!("@when({})", conditional.source) });
+            }
+            if let Some(assertion) = unkeyed.assertions.first() {
+                return Err(SchemaError::DirectiveOutsideRecord { text: format!("@assert({})", assertion.source) });
+            }
+            self.records.remove(0);
+        }
+        self.records.push(record);
+        Ok(())
     }
 
     pub fn from_text(text: &str) -> Result<Schema, SchemaErrors> {
         let mut schema = Schema::new();
         let mut errors = vec![];
 
-        let terms = text.split_whitespace();
-        for term in terms {
-            match Term::from_text(term) {
-                Ok(term) => schema.add_term(term),
-                Err(error) => errors.push(error),
+        let mut locale = None;
+
+        let mut tokens = split_tokens(text).into_iter();
+        while let Some(token) = tokens.next() {
+            if token.starts_with('@') {
+                let result = Directive::from_text(token).and_then(|directive| match directive {
+                    Directive::Record { key, role } => schema.add_record(Record::new(Some(key), role)),
+                    Directive::When { condition } => {
+                        let term = tokens.next().ok_or(SchemaError::MissingConditionalTerm)?;
+                        schema.add_conditional(Conditional {
+                            condition: Expr::from_text(&condition)?,
+                            source: condition,
+                            term: Term::from_text(term)?,
+                        });
+                        Ok(())
+                    }
+                    Directive::Assert { expression } => {
+                        schema.add_assertion(Assertion {
+                            expr: Expr::from_text(&expression)?,
+                            source: expression,
+                        });
+                        Ok(())
+                    }
+                    Directive::Check(check) => {
+                        schema.checks.push(check);
+                        Ok(())
+                    }
+                    Directive::Locale(format) => {
+                        locale = Some(format);
+                        Ok(())
+                    }
+                });
+                if let Err(error) = result {
+                    errors.push(error);
+                }
+                continue;
             }
+
+            let result = match token {
+                "..." => schema.set_rest(),
+                token => Term::from_text(token).and_then(|term| schema.add_term(term)),
+            };
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+
+        if let Some(format) = &locale {
+            schema.set_default_format(format);
         }
 
+        for record in &schema.records {
+            errors.extend(record.check());
+        }
+        errors.extend(schema.check_dataset());
+
         if errors.is_empty() {
             Ok(schema)
         } else {
@@ -41,27 +148,125 @@ impl Schema {
         }
     }
 
-    fn validate_line(self: &Schema, line: String) -> Result<(), ValidateLineErrors> {
-        let values = line.split_whitespace().collect::<Vec<&str>>();
-        if values.len() != self.terms.len() {
-            return Err(ValidateLineErrors(
-                vec![
-                    ValidateLineError::FieldNumberMismatch {
-                        expected: self.terms.len(),
-                        found: values.len()
-                    }
-                ]
-            ));
+    /// Apply the `@locale` number format to every number type without its
+    /// own format, wherever it is declared in the schema.
+    fn set_default_format(self: &mut Schema, format: &NumberFormat) {
+        for record in &mut self.records {
+            record.terms
+                .iter_mut()
+                .chain(record.conditionals.iter_mut().map(|conditional| &mut conditional.term))
+                .for_each(|term| term.set_default_format(format));
         }
+    }
 
+    fn trailer(self: &Schema) -> Option<&Record> {
+        self.records.iter().find(|record| record.role == Some(Role::Trailer))
+    }
+
+    /// Resolve the fields and columns referenced by `@trailer` checks, which
+    /// need a trailer record.
+    fn check_dataset(self: &Schema) -> Vec<SchemaError> {
         let mut errors = vec![];
+        let trailer = self.trailer();
+        if trailer.is_none() && self.checks.iter().any(|check| matches!(check, Check::Trailer { .. })) {
+            errors.push(SchemaError::MissingTrailerRecord);
+        }
+        let has_term = |record: &Record, name: &str| record.terms.iter().any(|term| term.name == name);
 
-        for (i, value) in values.iter().enumerate() {
-            let term = &self.terms[i];
-            match term.validate(value) {
-                Ok(_) => (),
-                Err(error) => errors.push(error),
+        for check in &self.checks {
+            let Check::Trailer { field, aggregate } = check else { continue };
+
+            if let (Field::Name(name), Some(trailer)) = (field, trailer) {
+                if !has_term(trailer, name) {
+                    errors.push(SchemaError::UnknownTerm { name: name.clone() });
+                }
+            }
+            if let Aggregate::Sum(column) = aggregate {
+                let mut rows = self.records
+                    .iter()
+                    .filter(|record| record.role.is_none());
+                if !rows.any(|record| has_term(record, column)) {
+                    errors.push(SchemaError::UnknownTerm { name: column.clone() });
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Select the record layout for a line by its first field.
+    fn record(self: &Schema, values: &[&str]) -> Result<&Record, ValidateLineErrors> {
+        if let [record] = self.records.as_slice() {
+            if record.key.is_none() {
+                return Ok(record);
+            }
+        }
+
+        let found = values.first().copied().unwrap_or_default();
+        self.records
+            .iter()
+            .find(|record| record.key.as_deref() == Some(found))
+            .ok_or_else(|| ValidateLineErrors(vec![ValidateLineError::UnknownRecord {
+                // The key is the first field, named by the first term
+                column: Column {
+                    name: self.records
+                        .iter()
+                        .find_map(|record| record.terms.first())
+                        .map(|term| term.name.clone())
+                        .unwrap_or_default(),
+                    index: 1,
+                    offset: 0,
+                },
+                expected: self.records
+                    .iter()
+                    .filter_map(|record| record.key.clone())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                found: found.to_string(),
+            }]))
+    }
+
+    fn validate_order(self: &Schema, record: &Record, is_first: bool, is_last: bool) -> Vec<ValidateLineError> {
+        let mut errors = vec![];
+
+        for expected in &self.records {
+            let Some(role) = expected.role else { continue };
+            let Some(key) = &expected.key else { continue };
+
+            let at_position = match role {
+                Role::Header => is_first,
+                Role::Trailer => is_last,
+            };
+            let is_expected = std::ptr::eq(expected, record);
+
+            if at_position && !is_expected {
+                errors.push(ValidateLineError::MissingRecord {
+                    role: role.to_string(),
+                    key: key.clone(),
+                    position: role.position().to_string(),
+                    found: record.key.clone().unwrap_or_default(),
+                });
             }
+            if !at_position && is_expected {
+                errors.push(ValidateLineError::MisplacedRecord {
+                    role: role.to_string(),
+                    key: key.clone(),
+                    position: role.position().to_string(),
+                });
+            }
+        }
+
+        errors
+    }
+
+    fn validate_line(self: &Schema, line: &str, is_first: bool, is_last: bool) -> Result<(), ValidateLineErrors> {
+        let fields = split_fields(line);
+        let values = fields.iter().map(|(_, value)| *value).collect::<Vec<&str>>();
+        let record = self.record(&values)?;
+
+        let mut errors = self.validate_order(record, is_first, is_last);
+        if let Err(line_errors) = record.validate(&fields) {
+            errors.extend(line_errors.0);
         }
 
         if errors.is_empty() {
@@ -71,29 +276,100 @@ impl Schema {
         }
     }
 
-    pub fn print_and_validate(self: &Schema, reader: Box<dyn BufRead>) -> Result<(), ValidationErrors> {
+    fn normalize(self: &Schema, line: &str) -> Option<String> {
+        let values = line.split_whitespace().collect::<Vec<&str>>();
+        self.record(&values).ok()?.normalize(line)
+    }
+
+    fn tally(self: &Schema, tally: &mut Tally, line: &str) {
+        let values = line.split_whitespace().collect::<Vec<&str>>();
+        let Ok(record) = self.record(&values) else { return };
+
+        match record.role {
+            Some(Role::Header) => {}
+            Some(Role::Trailer) => tally.set_trailer(&record.terms, &values),
+            None => tally.add_row(&record.terms, &values),
+        }
+    }
+
+    pub fn print_and_validate(self: &Schema, lines: &mut Lines, limits: &Limits) -> Result<(), ValidationErrors> {
         let mut errors = vec![];
+        let mut omitted = 0;
+        let mut tally = Tally::default();
 
-        for (i, line) in reader.lines().enumerate() {
-            let line = line.unwrap();
-            println!("{}", line);
+        let mut empty = true;
+        let mut lines = lines.peekable();
+        while let Some(line) = lines.next() {
+            empty = false;
+            let is_last = lines.peek().is_none();
 
-            match self.validate_line(line.clone()) {
+            // A line over the limit is not kept, so it is neither printed
+            // nor counted. Fields are counted before they are split, and a
+            // line with too many is printed as it is and not counted either.
+            let fields = line.text.split_whitespace().count();
+            let result = if line.too_long {
+                Err(ValidateLineErrors(vec![ValidateLineError::LineTooLong {
+                    limit: limits.max_line_length,
+                    length: line.length,
+                }]))
+            } else if fields > limits.max_fields {
+                print!("{}", line.raw);
+                Err(ValidateLineErrors(vec![ValidateLineError::TooManyFields {
+                    limit: limits.max_fields,
+                    found: fields,
+                }]))
+            } else {
+                match self.normalize(&line.text) {
+                    Some(normalized) => print!("{}", line.replace_text(&normalized)),
+                    None => print!("{}", line.raw),
+                }
+                if !self.checks.is_empty() {
+                    self.tally(&mut tally, &line.text);
+                }
+                self.validate_line(&line.text, line.number == 1, is_last)
+            };
+
+            match result {
                 Ok(_) => (),
+                Err(_) if errors.len() >= limits.max_errors => omitted += 1,
                 Err(line_errors) => errors.push(
                     ValidationError {
-                        line_number: i + 1,
-                        line_text: line,
+                        line_number: line.number,
+                        line_text: line.text,
                         errors: line_errors,
                     }
                 )
             }
         }
 
-        if errors.is_empty() {
+        let mut dataset = self.checks
+            .iter()
+            .filter_map(|check| check.validate(&tally).err())
+            .collect::<Vec<_>>();
+
+        // Without any line, the header and trailer are never checked above
+        if empty {
+            for record in &self.records {
+                let (Some(role), Some(key)) = (record.role, &record.key) else { continue };
+                dataset.push(DatasetError::MissingRecord {
+                    role: role.to_string(),
+                    key: key.clone(),
+                    position: role.position().to_string(),
+                });
+            }
+        }
+
+        if errors.is_empty() && omitted == 0 && dataset.is_empty() {
             Ok(())
         } else {
-            Err(ValidationErrors(errors))
+            Err(ValidationErrors {
+                lines: errors,
+                omitted: (omitted > 0).then_some(OmittedErrors {
+                    count: omitted,
+                    limit: limits.max_errors,
+                }),
+                dataset: DatasetErrors(dataset),
+            })
         }
     }
 }
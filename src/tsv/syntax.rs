@@ -0,0 +1,74 @@
+/// Split text on separators which are outside of parentheses and quoted
+/// strings. Empty pieces are kept only if `keep_empty` is set.
+fn split_top_level(text: &str, is_separator: impl Fn(char) -> bool, keep_empty: bool) -> Vec<&str> {
+    let mut pieces = vec![];
+    let mut start = None;
+    let mut depth = 0;
+    let mut quote = None;
+
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, c) if is_separator(c) && depth <= 0 => {
+                match start.take() {
+                    Some(s) => pieces.push(&text[s..i]),
+                    None if keep_empty => pieces.push(""),
+                    None => {}
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if start.is_none() {
+            start = Some(i);
+        }
+    }
+    match start {
+        Some(s) => pieces.push(&text[s..]),
+        None if keep_empty => pieces.push(""),
+        None => {}
+    }
+
+    pieces
+}
+
+/// Split schema text on whitespace, keeping directive arguments such as
+/// `@record(H, header)` and quoted strings in a single token.
+pub fn split_tokens(text: &str) -> Vec<&str> {
+    split_top_level(text, char::is_whitespace, false)
+}
+
+/// Split the types of a term such as `float(de)|null`.
+pub fn split_types(text: &str) -> Vec<&str> {
+    split_top_level(text, |c| c == '|', true)
+}
+
+/// Split comma separated arguments, trimming each of them.
+pub fn split_args(text: &str) -> Vec<&str> {
+    split_top_level(text, |c| c == ',', false)
+        .into_iter()
+        .map(|arg| arg.trim())
+        .filter(|arg| !arg.is_empty())
+        .collect()
+}
+
+/// Split `name(args)` into its name and raw arguments. Text without
+/// parentheses has empty arguments.
+pub fn split_call(text: &str) -> Option<(&str, &str)> {
+    match text.find('(') {
+        Some(i) => Some((&text[..i], text[i + 1..].strip_suffix(')')?)),
+        None => Some((text, "")),
+    }
+}
+
+/// Strip the quotes of `','` or `","`, leaving unquoted text as it is.
+pub fn unquote(text: &str) -> &str {
+    ['\'', '"']
+        .iter()
+        .find_map(|q| text.strip_prefix(*q).and_then(|text| text.strip_suffix(*q)))
+        .unwrap_or(text)
+}
@@ -1,12 +1,202 @@
-use super::error::{SchemaError, ValidateLineError};
+use crate::decimal::{Decimal, DecimalType};
+use super::charclass::CharClass;
+use super::checkdigit::CheckDigit;
+use super::error::{Column, SchemaError, ValidateLineError};
+use super::syntax::{split_args, split_call, split_types, unquote};
+use super::unit::{Quantity, UnitType};
+
+/// How integers and floats are written. A number type without a format
+/// accepts only what Rust's number parsing accepts.
+#[derive(Debug, PartialEq, Clone)]
+pub struct NumberFormat {
+    pub thousands: Option<char>,
+    pub decimal: char,
+    pub fullwidth: bool,
+}
+
+impl NumberFormat {
+    /// Build a format from a locale such as `en` or `de`, or from options
+    /// `thousands=','`, `decimal=','` and `fullwidth`.
+    pub fn from_args(args: &[&str]) -> Option<NumberFormat> {
+        let mut format = NumberFormat {
+            thousands: None,
+            decimal: '.',
+            fullwidth: false,
+        };
+
+        for arg in args {
+            match arg.split_once('=').map(|(k, v)| (k.trim(), unquote(v.trim()))) {
+                Some(("thousands", v)) => format.thousands = Some(single_char(v)?),
+                Some(("decimal", v)) => format.decimal = single_char(v)?,
+                Some(_) => return None,
+                None => match *arg {
+                    "en" => (format.thousands, format.decimal) = (Some(','), '.'),
+                    "de" => (format.thousands, format.decimal) = (Some('.'), ','),
+                    "ja" => (format.thousands, format.decimal, format.fullwidth) = (Some(','), '.', true),
+                    "fullwidth" => format.fullwidth = true,
+                    _ => return None,
+                },
+            }
+        }
+
+        if format.thousands == Some(format.decimal) {
+            return None;
+        }
+        Some(format)
+    }
+
+    /// Rewrite a number to the plain form Rust parses, or `None` if it is not
+    /// written in this format.
+    fn normalize(&self, value: &str) -> Option<String> {
+        let value = if self.fullwidth {
+            value.chars().map(halfwidth).collect::<String>()
+        } else {
+            value.to_string()
+        };
+
+        let (sign, unsigned) = match value.strip_prefix(['-', '+']) {
+            Some(unsigned) => (&value[..1], unsigned),
+            None => ("", value.as_str()),
+        };
+        let (integer, fraction) = match unsigned.split_once(self.decimal) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (unsigned, None),
+        };
+
+        let integer = match self.thousands {
+            Some(thousands) if integer.contains(thousands) => {
+                let groups = integer.split(thousands).collect::<Vec<&str>>();
+                let (head, tail) = groups.split_first()?;
+                let is_grouped = (1..=3).contains(&head.len())
+                    && tail.iter().all(|group| group.len() == 3);
+                if !is_grouped {
+                    return None;
+                }
+                groups.concat()
+            }
+            _ => integer.to_string(),
+        };
+
+        let digits = |text: &str| !text.is_empty() && text.chars().all(|c| c.is_ascii_digit());
+        if !digits(&integer) || !fraction.is_none_or(digits) {
+            return None;
+        }
+
+        Some(match fraction {
+            Some(fraction) => format!("{}{}.{}", sign, integer, fraction),
+            None => format!("{}{}", sign, integer),
+        })
+    }
+}
+
+impl std::fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut args = vec![];
+        if let Some(thousands) = self.thousands {
+            args.push(format!("thousands='{}'", thousands));
+        }
+        args.push(format!("decimal='{}'", self.decimal));
+        if self.fullwidth {
+            args.push("fullwidth".to_string());
+        }
+        write!(f, "{}", args.join(", "))
+    }
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// Map full-width digits and number symbols to their ASCII forms.
+fn halfwidth(c: char) -> char {
+    match c {
+        '０'..='９' | '－' | '＋' | '．' | '，' => char::from_u32(c as u32 - 0xFEE0).unwrap(),
+        c => c,
+    }
+}
+
+/// A number without rounding, for totals which must match exactly: an
+/// integer, a decimal as written, or a float which is neither.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Exact {
+    Integer(i128),
+    Decimal(Decimal),
+    Float(f64),
+}
+
+impl Exact {
+    /// Parse a field which has no number type.
+    pub fn parse(text: &str) -> Option<Exact> {
+        text.parse::<i128>().ok().map(Exact::Integer)
+            .or_else(|| Decimal::parse(text).map(Exact::Decimal))
+            .or_else(|| text.parse::<f64>().ok().map(Exact::Float))
+    }
+
+    fn decimal(&self) -> Option<Decimal> {
+        match self {
+            Exact::Integer(integer) => Decimal::parse(&integer.to_string()),
+            Exact::Decimal(decimal) => Some(decimal.clone()),
+            Exact::Float(_) => None,
+        }
+    }
+
+    fn float(&self) -> f64 {
+        match self {
+            Exact::Integer(integer) => *integer as f64,
+            Exact::Decimal(decimal) => decimal.to_string().parse().unwrap_or(f64::NAN),
+            Exact::Float(float) => *float,
+        }
+    }
+
+    /// The sum, which is exact unless either number is a float.
+    pub fn add(&self, other: &Exact) -> Exact {
+        if let (Exact::Integer(lhs), Exact::Integer(rhs)) = (self, other) {
+            if let Some(sum) = lhs.checked_add(*rhs) {
+                return Exact::Integer(sum);
+            }
+        }
+        match (self.decimal(), other.decimal()) {
+            (Some(lhs), Some(rhs)) => Exact::Decimal(lhs.add(&rhs)),
+            _ => Exact::Float(self.float() + other.float()),
+        }
+    }
+
+    /// Whether the numbers are equal, so that `10.50` equals `10.5`.
+    pub fn equals(&self, other: &Exact) -> bool {
+        match (self.decimal(), other.decimal()) {
+            (Some(lhs), Some(rhs)) => lhs.cmp(&rhs).is_eq(),
+            _ => self.float() == other.float(),
+        }
+    }
+}
+
+impl std::fmt::Display for Exact {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Exact::Integer(integer) => write!(f, "{}", integer),
+            Exact::Decimal(decimal) => write!(f, "{}", decimal),
+            Exact::Float(float) => write!(f, "{}", float),
+        }
+    }
+}
 
 #[derive(Debug, PartialEq)]
 pub enum Type {
-    Integer,
-    Float,
+    Integer(Option<NumberFormat>),
+    Float(Option<NumberFormat>),
+    Decimal(DecimalType),
+    Unit(UnitType),
+    Chars(CharClass),
+    CheckDigit(CheckDigit),
     String,
     Boolean,
     Null,
+    /// The type of a wildcard term, which accepts any field.
+    Any,
 }
 
 #[derive(Debug, PartialEq)]
@@ -18,13 +208,100 @@ pub struct Term {
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Type::Integer => write!(f, "integer"),
-            Type::Float => write!(f, "float"),
+            Type::Integer(None) => write!(f, "integer"),
+            Type::Integer(Some(format)) => write!(f, "integer({})", format),
+            Type::Float(None) => write!(f, "float"),
+            Type::Float(Some(format)) => write!(f, "float({})", format),
+            Type::Decimal(decimal) => write!(f, "{}", decimal),
+            Type::Unit(unit) => write!(f, "{}", unit),
+            Type::Chars(class) => write!(f, "{}", class),
+            Type::CheckDigit(check_digit) => write!(f, "{}", check_digit),
             Type::String => write!(f, "string"),
             Type::Boolean => write!(f, "boolean"),
             Type::Null => write!(f, "null"),
+            Type::Any => write!(f, "*"),
+        }
+    }
+}
+
+impl Type {
+    pub fn from_text(text: &str) -> Result<Type, SchemaError> {
+        let invalid = || SchemaError::InvalidType {
+            type_: text.to_string()
+        };
+
+        // Type names are case-insensitive, but arguments are not, so that
+        // units such as `Mb` and `MB` stay distinct
+        let (name, args) = split_call(text).ok_or_else(invalid)?;
+        let name = name.to_lowercase();
+        let args = split_args(args);
+
+        match (name.as_str(), args.as_slice()) {
+            ("integer", []) => Ok(Type::Integer(None)),
+            ("integer", args) => Ok(Type::Integer(Some(NumberFormat::from_args(args).ok_or_else(invalid)?))),
+            ("float", []) => Ok(Type::Float(None)),
+            ("float", args) => Ok(Type::Float(Some(NumberFormat::from_args(args).ok_or_else(invalid)?))),
+            ("decimal", args) => Ok(Type::Decimal(DecimalType::from_args(args).ok_or_else(invalid)?)),
+            ("duration", args) => Ok(Type::Unit(UnitType::from_args(Quantity::Duration, args).ok_or_else(invalid)?)),
+            ("bytesize", args) => Ok(Type::Unit(UnitType::from_args(Quantity::ByteSize { si: false }, args).ok_or_else(invalid)?)),
+            ("string", []) => Ok(Type::String),
+            ("boolean", []) => Ok(Type::Boolean),
+            ("null", []) => Ok(Type::Null),
+            (name, []) => match CheckDigit::from_name(name) {
+                Some(check_digit) => Ok(Type::CheckDigit(check_digit)),
+                None => Ok(Type::Chars(CharClass::from_name(name).ok_or_else(invalid)?)),
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            Type::Integer(_) | Type::Float(_) | Type::Decimal(_) | Type::Unit(_) => self.number(value).is_some(),
+            Type::Chars(class) => class.first_offending(value).is_none(),
+            Type::CheckDigit(check_digit) => check_digit.accepts(value),
+            Type::String => true,
+            Type::Boolean => matches!(value.to_lowercase().as_str(), "true" | "false"),
+            Type::Null => value == "_",
+            Type::Any => true,
         }
     }
+
+    /// The value of a number type without rounding, or `None` for other
+    /// types and values which are not numbers.
+    pub fn exact(&self, value: &str) -> Option<Exact> {
+        match self {
+            Type::Integer(format) => normalize(value, format)?.parse::<i64>().ok().map(|n| Exact::Integer(n as i128)),
+            Type::Float(format) => {
+                let normalized = normalize(value, format)?;
+                match Decimal::parse(&normalized) {
+                    Some(decimal) => Some(Exact::Decimal(decimal)),
+                    None => normalized.parse::<f64>().ok().map(Exact::Float),
+                }
+            }
+            Type::Decimal(decimal) if decimal.accepts(value) => Decimal::parse(value).map(Exact::Decimal),
+            _ => self.number(value).map(Exact::Float),
+        }
+    }
+
+    /// Parse a value of a number type, or `None` for other types and values
+    /// which are not numbers.
+    pub fn number(&self, value: &str) -> Option<f64> {
+        match self {
+            Type::Integer(format) => normalize(value, format)?.parse::<i64>().ok().map(|n| n as f64),
+            Type::Float(format) => normalize(value, format)?.parse::<f64>().ok(),
+            Type::Decimal(decimal) if decimal.accepts(value) => value.parse::<f64>().ok(),
+            Type::Unit(unit) => unit.value(value),
+            _ => None,
+        }
+    }
+}
+
+fn normalize(value: &str, format: &Option<NumberFormat>) -> Option<String> {
+    match format {
+        Some(format) => format.normalize(value),
+        None => Some(value.to_string()),
+    }
 }
 
 impl Term {
@@ -35,7 +312,17 @@ impl Term {
         }
     }
 
+    /// A term written as `*` or `_`, which matches a single field without
+    /// validating it.
+    pub fn wildcard() -> Term {
+        Term::new("*", vec![Type::Any])
+    }
+
     pub fn from_text(text: &str) -> Result<Term, SchemaError> {
+        if text == "*" || text == "_" {
+            return Ok(Term::wildcard());
+        }
+
         let inputs = text.split(":").collect::<Vec<&str>>();
         if inputs.len() != 2 {
             return Err(SchemaError::InvalidSyntax{
@@ -43,69 +330,88 @@ impl Term {
             });
         }
         let name = inputs[0].to_string();
-        let types = inputs[1].to_lowercase();
-
-        let types = types.split("|").map(|type_| {
-            match type_ {
-                "integer" => Ok(Type::Integer),
-                "float" => Ok(Type::Float),
-                "string" => Ok(Type::String),
-                "boolean" => Ok(Type::Boolean),
-                "null" => Ok(Type::Null),
-                _ => Err(SchemaError::InvalidType {
-                    type_: type_.to_string()
-                })
-            }
-        }).collect::<Result<Vec<Type>, SchemaError>>()?;
+        let types = split_types(inputs[1])
+            .into_iter()
+            .map(Type::from_text)
+            .collect::<Result<Vec<Type>, SchemaError>>()?;
 
         Ok(Term::new(name.as_str(), types))
     }
 
-    pub fn validate(&self, value: &str) -> Result<(), ValidateLineError> {
-        let mut is_valid = false;
-        for type_ in &self.types {
+    /// Apply a schema-wide number format to number types without their own.
+    pub fn set_default_format(&mut self, default: &NumberFormat) {
+        for type_ in &mut self.types {
             match type_ {
-                Type::Integer => {
-                    if value.parse::<i64>().is_ok() {
-                        is_valid = true;
-                        break;
-                    }
-                }
-                Type::Float => {
-                    if value.parse::<f64>().is_ok() {
-                        is_valid = true;
-                        break;
-                    }
-                }
-                Type::String => {
-                    is_valid = true;
-                    break;
-                }
-                Type::Boolean => {
-                    match value.to_lowercase().as_str() {
-                        "true" | "false" => {
-                            is_valid = true;
-                            break;
-                        }
-                        _ => {}
-                    }
-                }
-                Type::Null => {
-                    if value == "_" {
-                        is_valid = true;
-                        break;
-                    }
-                }
+                Type::Integer(format @ None) | Type::Float(format @ None) => *format = Some(default.clone()),
+                _ => {}
             }
         }
-        if !is_valid {
-            return Err(
-                ValidateLineError::DataTypeMismatch {
-                    type_: self.types.iter().map(|type_| type_.to_string()).collect::<Vec<String>>().join(" | "),
-                    value: value.to_string()
-                }
-            );
+    }
+
+    /// The value as an exact number, by the first number type which accepts
+    /// it.
+    pub fn exact(&self, value: &str) -> Option<Exact> {
+        self.types.iter().find_map(|type_| type_.exact(value))
+    }
+
+    /// The value as a number, by the first number type which accepts it.
+    pub fn number(&self, value: &str) -> Option<f64> {
+        self.types.iter().find_map(|type_| type_.number(value))
+    }
+
+    /// The value in the base unit of the first type accepting it, if that
+    /// type asks for normalization.
+    pub fn normalize(&self, value: &str) -> Option<String> {
+        self.types.iter().find(|type_| type_.accepts(value)).and_then(|type_| match type_ {
+            Type::Unit(unit) if unit.normalize => unit.value(value).map(|value| value.to_string()),
+            _ => None,
+        })
+    }
+
+    /// The types of the term, as `integer | null`.
+    pub fn type_text(&self) -> String {
+        self.types.iter().map(|type_| type_.to_string()).collect::<Vec<String>>().join(" | ")
+    }
+
+    pub fn accepts(&self, value: &str) -> bool {
+        self.types.iter().any(|type_| type_.accepts(value))
+    }
+
+    /// Why a value the term does not accept fails: a wrong check digit, an
+    /// offending character, or else its type.
+    pub fn mismatch(&self, value: &str, column: Column) -> ValidateLineError {
+        let type_ = self.type_text();
+        let offending = self.types.iter().find_map(|type_| match type_ {
+            Type::Chars(class) => class.first_offending(value),
+            _ => None,
+        });
+        let check_digits = self.types.iter().find_map(|type_| match type_ {
+            Type::CheckDigit(check_digit) => check_digit.check(value),
+            _ => None,
+        });
+        if let Some((expected, found)) = check_digits {
+            return ValidateLineError::CheckDigitMismatch {
+                column,
+                type_,
+                value: value.to_string(),
+                expected,
+                found,
+            };
+        }
+        match offending {
+            Some((position, character)) => ValidateLineError::InvalidCharacter {
+                column,
+                type_,
+                value: value.to_string(),
+                character,
+                code_point: format!("U+{:04X}", character as u32),
+                position,
+            },
+            None => ValidateLineError::DataTypeMismatch {
+                column,
+                type_,
+                value: value.to_string(),
+            },
         }
-        Ok(())
     }
 }
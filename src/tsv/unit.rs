@@ -0,0 +1,179 @@
+/// A quantity written with units, such as `2h30m` or `10MiB`, measured in
+/// a base unit: seconds for durations and bytes for sizes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Quantity {
+    Duration,
+    ByteSize {
+        si: bool,
+    },
+}
+
+/// `duration(...)` or `bytesize(...)` with optional bounds in any unit, and
+/// `normalize` to print values in the base unit.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnitType {
+    pub quantity: Quantity,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub normalize: bool,
+}
+
+impl Quantity {
+    fn name(&self) -> &'static str {
+        match self {
+            Quantity::Duration => "duration",
+            Quantity::ByteSize { .. } => "bytesize",
+        }
+    }
+
+    fn scale(&self, unit: &str) -> Option<f64> {
+        match self {
+            Quantity::Duration => match unit {
+                "ns" => Some(1e-9),
+                "us" | "µs" => Some(1e-6),
+                "ms" => Some(1e-3),
+                "s" => Some(1.0),
+                "m" => Some(60.0),
+                "h" => Some(3600.0),
+                "d" => Some(86400.0),
+                _ => None,
+            },
+            Quantity::ByteSize { si } => {
+                let (prefix, suffix) = match unit.char_indices().nth(1) {
+                    Some((i, _)) => unit.split_at(i),
+                    None => (unit, ""),
+                };
+                // Units are case-sensitive, so that bits such as `Mb` are
+                // not taken for bytes. Kilo is `k` in SI units and `K`
+                // otherwise.
+                let exponent = match (prefix, suffix) {
+                    ("" | "B", "") => return Some(1.0),
+                    ("k", "B") | ("K", _) => 1,
+                    ("M", _) => 2,
+                    ("G", _) => 3,
+                    ("T", _) => 4,
+                    ("P", _) => 5,
+                    ("E", _) => 6,
+                    _ => return None,
+                };
+                // A single letter follows `du -h`, which counts in 1024s
+                // unless `--si` is given.
+                let base: f64 = match suffix {
+                    "" if *si => 1000.0,
+                    "" | "iB" => 1024.0,
+                    "B" => 1000.0,
+                    _ => return None,
+                };
+                Some(base.powi(exponent))
+            }
+        }
+    }
+
+    /// Parse a value into the base unit. Durations can chain components as
+    /// in `2h30m`, each unit at most once and from the largest, sizes have a
+    /// single component.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        if text == "0" {
+            return Some(0.0);
+        }
+
+        let mut total = 0.0;
+        let mut rest = text;
+        let mut components = 0;
+        let mut previous = f64::INFINITY;
+        while !rest.is_empty() {
+            let number_end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+            let unit_end = rest[number_end..]
+                .find(|c: char| c.is_ascii_digit() || c == '.')
+                .map_or(rest.len(), |i| number_end + i);
+            let (number, unit) = (&rest[..number_end], &rest[number_end..unit_end]);
+
+            let is_number = !number.starts_with('.') && !number.ends_with('.') && number.matches('.').count() <= 1;
+            if number.is_empty() || !is_number {
+                return None;
+            }
+            let scale = self.scale(unit)?;
+            if scale >= previous {
+                return None;
+            }
+            previous = scale;
+            total += number.parse::<f64>().ok()? * scale;
+
+            components += 1;
+            rest = &rest[unit_end..];
+        }
+
+        match self {
+            Quantity::ByteSize { .. } if components > 1 => None,
+            Quantity::ByteSize { .. } => Some(total.round()),
+            Quantity::Duration => Some(total),
+        }
+    }
+}
+
+impl UnitType {
+    pub fn from_args(quantity: Quantity, args: &[&str]) -> Option<UnitType> {
+        let mut type_ = UnitType {
+            quantity,
+            min: None,
+            max: None,
+            normalize: false,
+        };
+
+        let mut bounds = vec![];
+        for arg in args {
+            match arg.split_once('=') {
+                Some((key, value)) => bounds.push((key.trim(), value.trim())),
+                None => match (*arg, quantity) {
+                    ("normalize", _) => type_.normalize = true,
+                    ("si", Quantity::ByteSize { .. }) => type_.quantity = Quantity::ByteSize { si: true },
+                    _ => return None,
+                },
+            }
+        }
+
+        // Bounds are read after `si`, which changes the meaning of their units
+        for (key, value) in bounds {
+            match key {
+                "min" => type_.min = Some(type_.quantity.parse(value)?),
+                "max" => type_.max = Some(type_.quantity.parse(value)?),
+                _ => return None,
+            }
+        }
+
+        match (type_.min, type_.max) {
+            (Some(min), Some(max)) if min > max => None,
+            _ => Some(type_),
+        }
+    }
+
+    /// The value in the base unit, if it is written correctly and in range.
+    pub fn value(&self, text: &str) -> Option<f64> {
+        let value = self.quantity.parse(text)?;
+        let in_range = self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max);
+        in_range.then_some(value)
+    }
+}
+
+impl std::fmt::Display for UnitType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut args = vec![];
+        if let Quantity::ByteSize { si: true } = self.quantity {
+            args.push("si".to_string());
+        }
+        if let Some(min) = self.min {
+            args.push(format!("min={}", min));
+        }
+        if let Some(max) = self.max {
+            args.push(format!("max={}", max));
+        }
+        if self.normalize {
+            args.push("normalize".to_string());
+        }
+
+        match args.is_empty() {
+            true => write!(f, "{}", self.quantity.name()),
+            false => write!(f, "{}({})", self.quantity.name(), args.join(", ")),
+        }
+    }
+}
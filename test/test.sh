@@ -61,6 +61,20 @@ run_test() {
   assert $1
 }
 
+# Compare stderr as it is, blanks included, for errors whose alignment matters
+run_exact_test() {
+  run_test $1
+
+  if ! diff $1/err.txt $tmp_stderr > /dev/null; then
+    printf "\033[1;31mTest $1 failed\033[0m\n"
+    echo
+    printf "\033[1;31mExpected stderr exactly: \n$(cat -A $1/err.txt)\033[0m\n"
+    echo
+    printf "\033[1;31mActual stderr          : \n$(cat -A $tmp_stderr) \033[0m\n"
+    exit 1
+  fi
+}
+
 run_test $test_dir/tsv/primitive/integer
 run_test $test_dir/tsv/primitive/integer_dont_accept_float
 run_test $test_dir/tsv/primitive/string
@@ -71,19 +85,100 @@ run_test $test_dir/tsv/primitive/float
 run_test $test_dir/tsv/primitive/float_accept_integer
 run_test $test_dir/tsv/primitive/float_invalid
 run_test $test_dir/tsv/primitive/null
+run_test $test_dir/tsv/primitive/decimal
+run_test $test_dir/tsv/primitive/decimal_invalid
+run_test $test_dir/tsv/primitive/decimal_min_over_max_error
+run_test $test_dir/tsv/primitive/duration
+run_test $test_dir/tsv/primitive/duration_invalid
+run_test $test_dir/tsv/primitive/duration_min_over_max_error
+run_test $test_dir/tsv/primitive/bytesize
+run_test $test_dir/tsv/primitive/bytesize_invalid
+run_test $test_dir/tsv/primitive/unit_case_invalid
+run_test $test_dir/tsv/primitive/charclass
+run_test $test_dir/tsv/primitive/charclass_invalid
+run_test $test_dir/tsv/primitive/checkdigit
+run_test $test_dir/tsv/primitive/checkdigit_invalid
 run_test $test_dir/tsv/multi_term_multi_column
 run_test $test_dir/tsv/sum_type
 run_test $test_dir/tsv/multiline_schema
+run_test $test_dir/tsv/normalize
+run_test $test_dir/tsv/wildcard/valid
+run_test $test_dir/tsv/wildcard/field_number_mismatch_error
 
 run_test $test_dir/tsv/multi_data_type_mismatch_error
 run_test $test_dir/tsv/field_number_mismatch_error
+run_exact_test $test_dir/tsv/caret_alignment_error
+
+# Record
+run_test $test_dir/tsv/record/valid
+run_test $test_dir/tsv/record/unknown_record_error
+run_test $test_dir/tsv/record/empty_input_error
+run_test $test_dir/tsv/record/term_outside_record_error
+run_test $test_dir/tsv/record/when_outside_record_error
+run_test $test_dir/tsv/record/assert_outside_record_error
+run_test $test_dir/tsv/record/order_error
+
+# Conditional
+run_test $test_dir/tsv/conditional/valid
+run_test $test_dir/tsv/conditional/conditional_mismatch_error
+
+# Assertion
+run_test $test_dir/tsv/assert/valid
+run_test $test_dir/tsv/assert/assertion_failed_error
+
+# Dataset
+run_test $test_dir/tsv/dataset/valid
+run_test $test_dir/tsv/dataset/trailer_mismatch_error
+run_test $test_dir/tsv/dataset/trailer_exact_error
+run_test $test_dir/tsv/dataset/trailer_decimal_valid
+run_test $test_dir/tsv/dataset/row_count_mismatch_error
+run_test $test_dir/tsv/dataset/rows_min_over_max_error
+
+# Locale
+run_test $test_dir/tsv/locale/per_term
+run_test $test_dir/tsv/locale/per_schema
+run_test $test_dir/tsv/locale/thousands_separator_error
+
+# Hygiene
+run_test $test_dir/tsv/hygiene/tolerate
+run_test $test_dir/tsv/hygiene/warn
+run_test $test_dir/tsv/hygiene/reject_error
+run_test $test_dir/tsv/hygiene/invalid_utf8_error
+run_test $test_dir/tsv/hygiene/invalid_utf8_after_error
+run_test $test_dir/tsv/hygiene/many_lines_error
+run_test $test_dir/json/hygiene/tolerate
+run_test $test_dir/json/hygiene/reject_error
+
+# Limits
+run_test $test_dir/tsv/limits/line_too_long_error
+run_test $test_dir/tsv/limits/line_too_long_crlf_error
+run_test $test_dir/tsv/limits/too_many_fields_error
+run_test $test_dir/tsv/limits/max_errors_zero_error
+run_test $test_dir/json/limits/document_too_large_error
+run_test $test_dir/json/limits/too_deep_error
+run_test $test_dir/json/limits/max_errors_error
 
 run_test $test_dir/json/primitive/string
 run_test $test_dir/json/primitive/string_invalid
+run_test $test_dir/json/primitive/string_refinement
+run_test $test_dir/json/primitive/string_refinement_invalid
+run_test $test_dir/json/primitive/date_time_fraction
+run_test $test_dir/json/primitive/date_time_fraction_invalid
+run_test $test_dir/json/primitive/string_min_over_max_error
 run_test $test_dir/json/primitive/number
+run_test $test_dir/json/primitive/integer
+run_test $test_dir/json/primitive/integer_invalid
+run_test $test_dir/json/primitive/integer_exact_invalid
+run_test $test_dir/json/primitive/integer_big
+run_test $test_dir/json/primitive/integer_big_invalid
+run_test $test_dir/json/primitive/integer_min_over_max_error
 run_test $test_dir/json/primitive/boolean
 run_test $test_dir/json/primitive/boolean_invalid
 run_test $test_dir/json/primitive/null
+run_test $test_dir/json/primitive/decimal
+run_test $test_dir/json/primitive/decimal_invalid
+run_test $test_dir/json/primitive/decimal_exact
+run_test $test_dir/json/primitive/decimal_exact_invalid
 
 run_test $test_dir/json/empty
 run_test $test_dir/json/multi_property
@@ -91,15 +186,68 @@ run_test $test_dir/json/nested_object
 
 run_test $test_dir/json/top_level_type
 run_test $test_dir/json/key_including_dash
+run_test $test_dir/json/key_with_unquoted_dash_error
 run_test $test_dir/json/key_with_quotes
 
 # Array
 run_test $test_dir/json/array/string
 run_test $test_dir/json/array/object
 run_test $test_dir/json/array/data_type_mismatch_error
+run_test $test_dir/json/array/constraint_valid
+run_test $test_dir/json/array/length_mismatch_error
+run_test $test_dir/json/array/duplicate_items_error
+run_test $test_dir/json/array/duplicate_numbers_error
+run_test $test_dir/json/array/min_over_max_error
 
 run_test $test_dir/json/sum_type
 
+# Union
+run_test $test_dir/json/union/valid
+run_test $test_dir/json/union/top_level
+run_test $test_dir/json/union/branch_error
+
+# Discriminated union
+run_test $test_dir/json/discriminated/valid
+run_test $test_dir/json/discriminated/branch_error
+run_test $test_dir/json/discriminated/unknown_discriminant_error
+
+# Optional
+run_test $test_dir/json/optional/valid
+run_test $test_dir/json/optional/null_error
+
+# Literal
+run_test $test_dir/json/literal/valid
+run_test $test_dir/json/literal/mismatch_error
+run_test $test_dir/json/literal/exact_mismatch_error
+
+# Tuple
+run_test $test_dir/json/tuple/valid
+run_test $test_dir/json/tuple/top_level
+run_test $test_dir/json/tuple/length_mismatch_error
+run_test $test_dir/json/tuple/element_error
+
+# Record
+run_test $test_dir/json/record/valid
+run_test $test_dir/json/record/unexpected_key_error
+run_test $test_dir/json/record/value_error
+
+# Definition
+run_test $test_dir/json/definition/valid
+run_test $test_dir/json/definition/recursive_error
+
+# Intersection
+run_test $test_dir/json/intersection/valid
+run_test $test_dir/json/intersection/error
+run_test $test_dir/json/intersection/reference_conflict_error
+run_test $test_dir/json/intersection/reference_closed
+run_test $test_dir/json/intersection/reordered_union
+run_test $test_dir/json/intersection/discriminated_error
+
+# Closed
+run_test $test_dir/json/closed/valid
+run_test $test_dir/json/closed/unexpected_property_error
+run_test $test_dir/json/closed/no_extra_properties_error
+
 # all
 run_test $test_dir/json/all
 